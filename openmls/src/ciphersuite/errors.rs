@@ -10,6 +10,7 @@ implement_error! {
         UnsupportedSignatureScheme = "This SignatureScheme is not supported.",
         KdfLabelTooLarge = "The requested Kdf label length is too large.",
         KdfSerializationError = "Serialization of the Kdf label failed.",
+        InvalidKdfLabel = "The Kdf label must be between 7 and 255 bytes long.",
         HkdfOutputLengthInvalid = "The requested HKDF output length is invalid",
         InsufficientRandomness = "Error getting enough randomness",
         InvalidSignature = "The signature could not be verified",
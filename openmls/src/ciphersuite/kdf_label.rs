@@ -1,7 +1,15 @@
 use super::*;
 
 /// `KdfLabel` is later serialized and used in the `label` field of
-/// `kdf_expand_label`.
+/// `expand_with_label`.
+///
+/// ```text
+/// struct {
+///     uint16 length = Length;
+///     opaque label<7..255> = "mls10 " + Label;
+///     opaque context<0..2^32-1> = Context;
+/// } KDFLabel;
+/// ```
 #[derive(TlsSerialize, TlsSize)]
 pub(in crate::ciphersuite) struct KdfLabel {
     length: u16,
@@ -9,6 +17,10 @@ pub(in crate::ciphersuite) struct KdfLabel {
     context: TlsByteVecU32,
 }
 
+/// Bounds of the length of the `label` field in the [`KdfLabel`].
+const MIN_LABEL_LENGTH: usize = 7;
+const MAX_LABEL_LENGTH: usize = 255;
+
 impl KdfLabel {
     /// Serialize this label.
     /// Returns the serialized label as byte vector or returns a [`CryptoError`]
@@ -25,6 +37,9 @@ impl KdfLabel {
             );
             return Err(CryptoError::KdfLabelTooLarge);
         }
+        if !(MIN_LABEL_LENGTH..=MAX_LABEL_LENGTH).contains(&label.len()) {
+            return Err(CryptoError::InvalidKdfLabel);
+        }
         log::trace!(
             "KDF Label:\n length: {:?}\n label: {:?}\n context: {:x?}",
             length as u16,
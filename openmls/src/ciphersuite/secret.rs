@@ -164,7 +164,12 @@ impl Secret {
 
     /// Expand a `Secret` to a new `Secret` of length `length` including a
     /// `label` and a `context`.
-    pub(crate) fn kdf_expand_label(
+    ///
+    /// The `label` is prefixed with the MLS version (e.g. `"mls10 "`), which
+    /// is why call sites only pass the bare label. Returns a
+    /// [`CryptoError::InvalidKdfLabel`] if the resulting label doesn't fit
+    /// into the `KdfLabel` structure.
+    pub(crate) fn expand_with_label(
         &self,
         backend: &impl OpenMlsCryptoProvider,
         label: &str,
@@ -198,7 +203,7 @@ impl Secret {
             label,
             self.ciphersuite.name()
         );
-        self.kdf_expand_label(backend, label, &[], self.ciphersuite.hash_length())
+        self.expand_with_label(backend, label, &[], self.ciphersuite.hash_length())
     }
 
     /// Update the ciphersuite and MLS version of this secret.
@@ -36,6 +36,29 @@ impl Signature {
     }
 }
 
+impl Signature {
+    /// Returns `true` if the length of the signature is plausible for the
+    /// given `signature_scheme`. EdDSA signatures have a fixed length, while
+    /// the length of DER encoded ECDSA signatures is only bounded.
+    pub(crate) fn has_valid_length(&self, signature_scheme: SignatureScheme) -> bool {
+        let length = self.value.as_slice().len();
+        match signature_scheme {
+            SignatureScheme::ED25519 => length == 64,
+            SignatureScheme::ED448 => length == 114,
+            SignatureScheme::ECDSA_SECP256R1_SHA256 => (8..=72).contains(&length),
+            SignatureScheme::ECDSA_SECP521R1_SHA512 => (8..=139).contains(&length),
+        }
+    }
+}
+
+impl From<Vec<u8>> for Signature {
+    fn from(value: Vec<u8>) -> Self {
+        Self {
+            value: value.into(),
+        }
+    }
+}
+
 impl<T> SignedStruct<T> for Signature {
     fn from_payload(_payload: T, signature: Signature) -> Self {
         signature
@@ -1,8 +1,9 @@
 use openmls_rust_crypto::OpenMlsRustCrypto;
 
 use crate::{
-    ciphersuite::{Ciphersuite, Secret},
+    ciphersuite::{Ciphersuite, CryptoError, Secret},
     config::ProtocolVersion,
+    test_utils::hex_to_bytes,
 };
 
 #[test]
@@ -32,3 +33,30 @@ fn secret_incompatible() {
     // This must panic because the two secrets have incompatible MLS versions.
     let _default_extracted = default_secret.hkdf_extract(crypto, &draft_secret);
 }
+
+#[test]
+fn expand_with_label_kat() {
+    let crypto = &OpenMlsRustCrypto::default();
+
+    // HKDF-SHA256 with the serialized `KdfLabel` for the label
+    // "mls10 test label" as info.
+    let secret = Secret::from(
+        hex_to_bytes("000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f").as_slice(),
+    );
+    let expanded = secret
+        .expand_with_label(crypto, "test label", b"test context", 16)
+        .expect("Error expanding secret.");
+    assert_eq!(
+        expanded.as_slice(),
+        hex_to_bytes("687200f5e23c9d2bc6d4fc21a25defbb").as_slice()
+    );
+
+    // The prefixed label must not exceed 255 bytes.
+    let too_long_label = "a".repeat(250);
+    assert_eq!(
+        secret
+            .expand_with_label(crypto, &too_long_label, &[], 16)
+            .expect_err("Expanding with a too long label must fail."),
+        CryptoError::InvalidKdfLabel
+    );
+}
@@ -131,6 +131,19 @@ impl MlsCiphertext {
         epoch_secrets: &EpochSecrets,
         secret_tree: &mut SecretTree,
     ) -> Result<VerifiableMlsPlaintext, MlsCiphertextError> {
+        self.to_plaintext_with_sender_data(ciphersuite, backend, epoch_secrets, secret_tree)
+            .map(|(verifiable, _sender_data)| verifiable)
+    }
+
+    /// Like [`MlsCiphertext::to_plaintext()`], but also returns the decrypted
+    /// [`SenderData`].
+    pub(crate) fn to_plaintext_with_sender_data(
+        &self,
+        ciphersuite: &Ciphersuite,
+        backend: &impl OpenMlsCryptoProvider,
+        epoch_secrets: &EpochSecrets,
+        secret_tree: &mut SecretTree,
+    ) -> Result<(VerifiableMlsPlaintext, SenderData), MlsCiphertextError> {
         log::debug!("Decrypting MlsCiphertext");
         // Check the ciphertext has the correct wire format
         if self.wire_format != WireFormat::MlsCiphertext {
@@ -238,7 +251,7 @@ impl MlsCiphertext {
             mls_ciphertext_content.confirmation_tag,
             None, /* MlsCiphertexts don't carry along the membership tag. */
         );
-        Ok(verifiable)
+        Ok((verifiable, SenderData::from(&sender_data)))
     }
 
     /// Returns `true` if this is a handshake message and `false` otherwise.
@@ -333,6 +346,36 @@ impl MlsSenderData {
     }
 }
 
+/// The sender information recovered from the encrypted sender data of an
+/// [`MlsCiphertext`], i.e. the leaf index of the sender and the generation
+/// of the key that was used to encrypt the message.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SenderData {
+    sender: LeafIndex,
+    generation: u32,
+}
+
+impl SenderData {
+    /// Get the leaf index of the sender.
+    pub fn sender(&self) -> LeafIndex {
+        self.sender
+    }
+
+    /// Get the generation of the key used to encrypt the message.
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+impl From<&MlsSenderData> for SenderData {
+    fn from(sender_data: &MlsSenderData) -> Self {
+        SenderData {
+            sender: sender_data.sender,
+            generation: sender_data.generation,
+        }
+    }
+}
+
 #[derive(Clone, TlsDeserialize, TlsSerialize, TlsSize)]
 pub(crate) struct MlsSenderDataAad {
     pub(crate) group_id: GroupId,
@@ -267,6 +267,9 @@ impl tls_codec::Serialize for MlsMessageIn {
     }
 }
 
+/// Deserializing an [`MlsMessageIn`] from a `&mut &[u8]` only consumes the
+/// bytes of a single message and leaves the remainder in the slice. See also
+/// [`MlsMessageIn::tls_deserialize_with_len()`].
 impl tls_codec::Deserialize for MlsMessageIn {
     fn tls_deserialize<R: Read>(bytes: &mut R) -> Result<Self, tls_codec::Error> {
         // Determine the wire format by looking at the first byte
@@ -1,4 +1,5 @@
 use super::*;
+use tls_codec::Deserialize;
 
 /// Unified message type for incoming MLS messages
 #[derive(Debug, Clone)]
@@ -34,6 +35,17 @@ impl MlsMessageIn {
             MlsMessageIn::Plaintext(m) => m.epoch(),
         }
     }
+
+    /// Deserialize the first message in `bytes` and return it together with
+    /// the number of bytes that were consumed.
+    ///
+    /// This allows parsing a buffer that contains several messages back to
+    /// back by advancing the buffer by the returned length after each call.
+    pub fn tls_deserialize_with_len(bytes: &[u8]) -> Result<(Self, usize), tls_codec::Error> {
+        let mut remainder = bytes;
+        let message = Self::tls_deserialize(&mut remainder)?;
+        Ok((message, bytes.len() - remainder.len()))
+    }
 }
 
 /// Unified message type for outgoing MLS messages
@@ -92,7 +92,7 @@ impl MlsPlaintext {
     }
 
     #[cfg(test)]
-    pub(super) fn set_content(&mut self, content: MlsPlaintextContentType) {
+    pub(crate) fn set_content(&mut self, content: MlsPlaintextContentType) {
         self.content = content;
     }
 
@@ -327,6 +327,20 @@ impl MlsPlaintext {
         self.confirmation_tag.as_ref()
     }
 
+    /// Replace the confirmation tag with one computed from the given
+    /// `confirmation_key` and `confirmed_transcript_hash`.
+    /// Together with [`ConfirmationKey::from_secret()`] this allows building
+    /// commits with a controlled confirmation tag in tests.
+    #[cfg(any(feature = "test-utils", test))]
+    pub(crate) fn re_tag(
+        &mut self,
+        backend: &impl OpenMlsCryptoProvider,
+        confirmation_key: &ConfirmationKey,
+        confirmed_transcript_hash: &[u8],
+    ) {
+        self.set_confirmation_tag(confirmation_key.tag(backend, confirmed_transcript_hash))
+    }
+
     /// The the authenticated data of this MlsPlaintext as byte slice.
     pub fn authenticated_data(&self) -> &[u8] {
         self.authenticated_data.as_slice()
@@ -565,6 +579,48 @@ impl VerifiableMlsPlaintext {
         Ok(())
     }
 
+    /// Verify the membership tags of a batch of `UnverifiedMlsPlaintext`s
+    /// against the same `membership_key`, e.g. when a delivery service filters
+    /// messages of one epoch. The `serialized_context` is set on every message
+    /// before verification. Returns for each message, in the same order,
+    /// whether its membership tag is valid. Credentials are not looked up.
+    pub fn verify_membership_batch(
+        msgs: &mut [Self],
+        serialized_context: &[u8],
+        membership_key: &MembershipKey,
+        backend: &impl OpenMlsCryptoProvider,
+    ) -> Vec<bool> {
+        msgs.iter_mut()
+            .map(|msg| {
+                msg.set_context(serialized_context.to_vec());
+                msg.verify_membership(backend, membership_key).is_ok()
+            })
+            .collect()
+    }
+
+    /// Verify only the signature of the message against the `credential` of
+    /// the claimed sender and skip the membership tag. Returns the verified
+    /// [`MlsPlaintext`] if successful or `MlsPlaintextError` otherwise. Note,
+    /// that the context must have been set before calling this function.
+    ///
+    /// This is meant for proposals that are stored until they are committed,
+    /// e.g. by a client that doesn't hold the membership key of the epoch.
+    /// It is only safe to use if the proposal is validated again when the
+    /// Commit referencing it is staged, since the signature alone doesn't
+    /// prove that the sender is a member of the group.
+    pub fn verify_signature_only(
+        &self,
+        backend: &impl OpenMlsCryptoProvider,
+        credential: &Credential,
+    ) -> Result<MlsPlaintext, MlsPlaintextError> {
+        self.clone()
+            .verify(backend, credential)
+            .map_err(|e| match e {
+                CredentialError::InvalidSignature => MlsPlaintextError::InvalidSignature,
+                e => e.into(),
+            })
+    }
+
     /// Get the sender.
     pub fn sender(&self) -> &Sender {
         &self.tbs.sender
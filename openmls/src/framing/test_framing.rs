@@ -2,7 +2,7 @@ use mls_group::create_commit_params::CreateCommitParams;
 use mls_group::proposals::ProposalStore;
 use mls_group::proposals::StagedProposal;
 use openmls_rust_crypto::OpenMlsRustCrypto;
-use tls_codec::{Deserialize, Serialize};
+use tls_codec::{Deserialize, Serialize, Size};
 
 use crate::framing::*;
 use crate::prelude::KeyPackageBundle;
@@ -143,6 +143,76 @@ fn codec_ciphertext() {
     }
 }
 
+/// This tests parsing several messages that were concatenated into a single
+/// buffer.
+#[test]
+fn codec_concatenated_messages() {
+    let crypto = OpenMlsRustCrypto::default();
+
+    for ciphersuite in Config::supported_ciphersuites() {
+        let credential_bundle = CredentialBundle::new(
+            vec![7, 8, 9],
+            CredentialType::Basic,
+            ciphersuite.signature_scheme(),
+            &crypto,
+        )
+        .unwrap();
+        let sender = Sender {
+            sender_type: SenderType::Member,
+            sender: LeafIndex::from(2u32),
+        };
+
+        let plaintexts: Vec<MlsPlaintext> = [vec![4, 5, 6], vec![7, 8, 9, 10]]
+            .iter()
+            .map(|application_data| {
+                MlsPlaintextTbs::new(
+                    WireFormat::MlsPlaintext,
+                    GroupId::random(&crypto),
+                    GroupEpoch(1u64),
+                    sender,
+                    vec![1, 2, 3].into(),
+                    Payload {
+                        content_type: ContentType::Application,
+                        payload: MlsPlaintextContentType::Application(
+                            application_data.clone().into(),
+                        ),
+                    },
+                )
+                .sign(&crypto, &credential_bundle)
+                .expect("Signing failed.")
+            })
+            .collect();
+
+        let mut buffer = Vec::new();
+        for plaintext in plaintexts.iter() {
+            buffer.extend(plaintext.tls_serialize_detached().unwrap());
+        }
+        let first_len = plaintexts[0].tls_serialized_len();
+
+        // Parse with a cursor that is advanced past each message.
+        let mut cursor = buffer.as_slice();
+        let first = MlsMessageIn::tls_deserialize(&mut cursor).expect("Could not decode message.");
+        assert_eq!(cursor.len(), buffer.len() - first_len);
+        let second = MlsMessageIn::tls_deserialize(&mut cursor).expect("Could not decode message.");
+        assert!(cursor.is_empty());
+
+        for (message, plaintext) in [first, second].iter().zip(plaintexts.iter()) {
+            assert_eq!(
+                message.tls_serialize_detached().unwrap(),
+                plaintext.tls_serialize_detached().unwrap()
+            );
+        }
+
+        // Parse by advancing the buffer by the consumed length.
+        let (_first, consumed) =
+            MlsMessageIn::tls_deserialize_with_len(&buffer).expect("Could not decode message.");
+        assert_eq!(consumed, first_len);
+        let (_second, consumed) = MlsMessageIn::tls_deserialize_with_len(&buffer[consumed..])
+            .expect("Could not decode message.");
+        assert_eq!(consumed, buffer.len() - first_len);
+    }
+}
+
 /// This tests the correctness of wire format checks
 #[test]
 fn wire_format_checks() {
@@ -320,6 +390,125 @@ fn membership_tag() {
     }
 }
 
+#[test]
+fn membership_tag_batch() {
+    let crypto = &OpenMlsRustCrypto::default();
+
+    for ciphersuite in Config::supported_ciphersuites() {
+        let credential_bundle = CredentialBundle::new(
+            vec![7, 8, 9],
+            CredentialType::Basic,
+            ciphersuite.signature_scheme(),
+            crypto,
+        )
+        .unwrap();
+        let group_context =
+            GroupContext::new(GroupId::random(crypto), GroupEpoch(1), vec![], vec![], &[]).unwrap();
+        let membership_key = MembershipKey::from_secret(Secret::random(
+            ciphersuite,
+            crypto,
+            None, /* MLS version */
+        ));
+        let serialized_context: Vec<u8> = group_context.tls_serialize_detached().unwrap();
+        let new_verifiable_plaintext = |content: &[u8]| {
+            let mls_plaintext = MlsPlaintext::new_application(
+                LeafIndex::from(2u32),
+                &[1, 2, 3],
+                content,
+                &credential_bundle,
+                &group_context,
+                &membership_key,
+                crypto,
+            )
+            .unwrap();
+            // The batch verification sets the context.
+            VerifiableMlsPlaintext::from_plaintext(mls_plaintext, vec![])
+        };
+
+        let valid = new_verifiable_plaintext(&[4, 5, 6]);
+        let mut tampered = new_verifiable_plaintext(&[4, 5, 6]);
+        let mut other_tag = new_verifiable_plaintext(&[7, 8, 9])
+            .membership_tag()
+            .clone()
+            .expect("There should have been a membership tag.");
+        tampered.set_membership_tag_test(other_tag.clone());
+        let mut missing = new_verifiable_plaintext(&[4, 5, 6]);
+        missing.unset_membership_tag();
+        other_tag.0.mac_value[0] ^= 0xFF;
+        let mut flipped = new_verifiable_plaintext(&[4, 5, 6]);
+        flipped.set_membership_tag_test(other_tag);
+
+        let mut msgs = vec![valid.clone(), tampered, valid, missing, flipped];
+        assert_eq!(
+            VerifiableMlsPlaintext::verify_membership_batch(
+                &mut msgs,
+                &serialized_context,
+                &membership_key,
+                crypto,
+            ),
+            vec![true, false, true, false, false]
+        );
+        assert!(msgs.iter().all(|msg| msg.has_context()));
+    }
+}
+
+#[test]
+fn proposal_signature_only() {
+    let crypto = &OpenMlsRustCrypto::default();
+
+    for ciphersuite in Config::supported_ciphersuites() {
+        let alice_credential_bundle = CredentialBundle::new(
+            "Alice".into(),
+            CredentialType::Basic,
+            ciphersuite.signature_scheme(),
+            crypto,
+        )
+        .unwrap();
+        let bob_credential_bundle = CredentialBundle::new(
+            "Bob".into(),
+            CredentialType::Basic,
+            ciphersuite.signature_scheme(),
+            crypto,
+        )
+        .unwrap();
+        let group_context =
+            GroupContext::new(GroupId::random(crypto), GroupEpoch(1), vec![], vec![], &[]).unwrap();
+        // Only the sender knows the membership key.
+        let membership_key = MembershipKey::from_secret(Secret::random(
+            ciphersuite,
+            crypto,
+            None, /* MLS version */
+        ));
+        let mls_plaintext = MlsPlaintext::new_proposal(
+            FramingParameters::new(&[], WireFormat::MlsPlaintext),
+            LeafIndex::from(0u32),
+            Proposal::Remove(RemoveProposal { removed: 1 }),
+            &alice_credential_bundle,
+            &group_context,
+            &membership_key,
+            crypto,
+        )
+        .unwrap();
+        let serialized_context: Vec<u8> = group_context.tls_serialize_detached().unwrap();
+        let verifiable_mls_plaintext =
+            VerifiableMlsPlaintext::from_plaintext(mls_plaintext.clone(), serialized_context);
+
+        // The signature verifies against the sender's credential.
+        let verified = verifiable_mls_plaintext
+            .verify_signature_only(crypto, alice_credential_bundle.credential())
+            .expect("Error verifying the proposal signature.");
+        assert_eq!(verified, mls_plaintext);
+
+        // It doesn't verify against another credential.
+        assert_eq!(
+            verifiable_mls_plaintext
+                .verify_signature_only(crypto, bob_credential_bundle.credential())
+                .expect_err("Verification with the wrong credential must fail."),
+            MlsPlaintextError::InvalidSignature
+        );
+    }
+}
+
 #[test]
 fn unknown_sender() {
     let crypto = &OpenMlsRustCrypto::default();
@@ -574,6 +763,31 @@ fn unknown_sender() {
             received_message.unwrap_err(),
             MlsGroupError::MlsCiphertextError(MlsCiphertextError::GenerationOutOfBound)
         );
+
+        // Alice sends a commit with a sender that points to a blank leaf or
+        // to a leaf outside of the tree
+        // Expected result: MlsPlaintextError::UnknownSender
+        proposal_store.empty();
+        let params = CreateCommitParams::builder()
+            .framing_parameters(framing_parameters)
+            .credential_bundle(&alice_credential_bundle)
+            .proposal_store(&proposal_store)
+            .force_self_update(false)
+            .build();
+        let (mut commit, _welcome_option, _kpb_option) = group_alice
+            .create_commit(params, crypto)
+            .expect("Error creating Commit");
+
+        for bogus_sender in [LeafIndex::from(1usize), LeafIndex::from(100usize)] {
+            commit.set_sender(Sender::member(bogus_sender));
+            let error = group_charlie
+                .stage_commit(&commit, &proposal_store, &[], None, crypto)
+                .expect_err("Staged a commit from an unknown sender.");
+            assert_eq!(
+                error,
+                MlsGroupError::MlsPlaintextError(MlsPlaintextError::UnknownSender)
+            );
+        }
     }
 }
 
@@ -662,6 +876,69 @@ fn confirmation_tag_presence() {
     }
 }
 
+/// This tests re-tagging a commit with a forced confirmation key.
+#[test]
+fn forced_confirmation_key() {
+    let crypto = &OpenMlsRustCrypto::default();
+
+    for ciphersuite in Config::supported_ciphersuites() {
+        let framing_parameters = FramingParameters::new(&[], WireFormat::MlsPlaintext);
+
+        let alice_credential_bundle = CredentialBundle::new(
+            "Alice".into(),
+            CredentialType::Basic,
+            ciphersuite.signature_scheme(),
+            crypto,
+        )
+        .unwrap();
+        let alice_key_package_bundle = KeyPackageBundle::new(
+            &[ciphersuite.name()],
+            &alice_credential_bundle,
+            crypto,
+            Vec::new(),
+        )
+        .unwrap();
+
+        let group_alice = MlsGroup::builder(GroupId::random(crypto), alice_key_package_bundle)
+            .build(crypto)
+            .expect("Error creating group.");
+
+        let proposal_store = ProposalStore::new();
+        let params = CreateCommitParams::builder()
+            .framing_parameters(framing_parameters)
+            .credential_bundle(&alice_credential_bundle)
+            .proposal_store(&proposal_store)
+            .build();
+        let (mut commit, _welcome_option, _kpb_option) = group_alice
+            .create_commit(params, crypto)
+            .expect("Error creating Commit");
+
+        // Force the confirmation key to a known value and re-tag the commit.
+        let forced_key = ConfirmationKey::from_secret(Secret::from_slice(
+            &vec![0x42; ciphersuite.hash_length()],
+            ProtocolVersion::default(),
+            ciphersuite,
+        ));
+        let transcript_hash = vec![0x17; ciphersuite.hash_length()];
+        commit.re_tag(crypto, &forced_key, &transcript_hash);
+
+        // The tag verifies with the forced key only.
+        assert_eq!(
+            commit.confirmation_tag(),
+            Some(&forced_key.tag(crypto, &transcript_hash))
+        );
+        let other_key = ConfirmationKey::from_secret(Secret::from_slice(
+            &vec![0x23; ciphersuite.hash_length()],
+            ProtocolVersion::default(),
+            ciphersuite,
+        ));
+        assert_ne!(
+            commit.confirmation_tag(),
+            Some(&other_key.tag(crypto, &transcript_hash))
+        );
+    }
+}
+
 ctest_ciphersuites!(invalid_plaintext_signature,test (ciphersuite_name: CiphersuiteName) {
 
         let crypto = &OpenMlsRustCrypto::default();
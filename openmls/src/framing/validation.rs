@@ -48,6 +48,7 @@ use super::*;
 ///  - ValSem9
 pub struct DecryptedMessage {
     plaintext: VerifiableMlsPlaintext,
+    sender_data: Option<SenderData>,
 }
 
 impl DecryptedMessage {
@@ -56,7 +57,7 @@ impl DecryptedMessage {
         inbound_message: MlsMessageIn,
     ) -> Result<Self, ValidationError> {
         if let MlsMessageIn::Plaintext(plaintext) = inbound_message {
-            Self::from_plaintext(plaintext)
+            Self::from_plaintext(plaintext, None)
         } else {
             Err(ValidationError::WrongWireFormat)
         }
@@ -72,9 +73,13 @@ impl DecryptedMessage {
         secret_tree: &mut SecretTree,
     ) -> Result<Self, ValidationError> {
         if let MlsMessageIn::Ciphertext(ciphertext) = inbound_message {
-            let plaintext =
-                ciphertext.to_plaintext(ciphersuite, backend, epoch_secrets, secret_tree)?;
-            Self::from_plaintext(plaintext)
+            let (plaintext, sender_data) = ciphertext.to_plaintext_with_sender_data(
+                ciphersuite,
+                backend,
+                epoch_secrets,
+                secret_tree,
+            )?;
+            Self::from_plaintext(plaintext, Some(sender_data))
         } else {
             Err(ValidationError::WrongWireFormat)
         }
@@ -84,7 +89,10 @@ impl DecryptedMessage {
     // - Confirmation tag must be present for Commit messages
     // - Membership tag must be present for member messages, if the original incoming message was not an MlsCiphertext
     // - Ensures application messages were originally MlsCiphertext messages
-    fn from_plaintext(plaintext: VerifiableMlsPlaintext) -> Result<Self, ValidationError> {
+    fn from_plaintext(
+        plaintext: VerifiableMlsPlaintext,
+        sender_data: Option<SenderData>,
+    ) -> Result<Self, ValidationError> {
         // ValSem7
         if plaintext.sender().is_member()
             && plaintext.wire_format() != WireFormat::MlsCiphertext
@@ -106,7 +114,10 @@ impl DecryptedMessage {
                 return Err(ValidationError::LibraryError);
             }
         }
-        Ok(DecryptedMessage { plaintext })
+        Ok(DecryptedMessage {
+            plaintext,
+            sender_data,
+        })
     }
 
     /// Returns the wire format
@@ -124,6 +135,11 @@ impl DecryptedMessage {
         self.plaintext.content_type()
     }
 
+    /// Returns the sender data if the message was an [MlsCiphertext].
+    pub fn sender_data(&self) -> Option<&SenderData> {
+        self.sender_data.as_ref()
+    }
+
     /// Returns the plaintext
     pub(crate) fn plaintext(&self) -> &VerifiableMlsPlaintext {
         &self.plaintext
@@ -138,6 +154,7 @@ pub struct UnverifiedMessage {
     plaintext: VerifiableMlsPlaintext,
     credential: Option<Credential>,
     aad_option: Option<Vec<u8>>,
+    sender_data: Option<SenderData>,
 }
 
 impl UnverifiedMessage {
@@ -150,6 +167,7 @@ impl UnverifiedMessage {
             plaintext: decrypted_message.plaintext,
             credential,
             aad_option: None,
+            sender_data: decrypted_message.sender_data,
         }
     }
 
@@ -168,6 +186,14 @@ impl UnverifiedMessage {
         self.credential.as_ref()
     }
 
+    /// Returns the sender data that was decrypted from the original
+    /// [MlsCiphertext]. This identifies the sender's leaf even though the
+    /// sender is hidden on the wire. Returns `None` for [MlsPlaintext]
+    /// messages.
+    pub fn sender_data(&self) -> Option<&SenderData> {
+        self.sender_data.as_ref()
+    }
+
     /// Decomposes an [UnverifiedMessage] into its parts.
     pub(crate) fn into_parts(self) -> (VerifiableMlsPlaintext, Option<Credential>) {
         (self.plaintext, self.credential)
@@ -13,7 +13,9 @@ use crate::framing::errors::{
 use crate::key_packages::KeyPackageError;
 use crate::messages::errors::ProposalQueueError;
 use crate::schedule::errors::{KeyScheduleError, PskSecretError};
-use crate::tree::{treemath::TreeMathError, ParentHashError, TreeError};
+use crate::tree::{
+    secret_tree::SecretTreeError, treemath::TreeMathError, ParentHashError, TreeError,
+};
 use tls_codec::Error as TlsCodecError;
 
 implement_error! {
@@ -22,6 +24,10 @@ implement_error! {
             InitSecretNotFound =
                 "Missing init secret when creating commit.",
             NoSignatureKey = "No signature key was found.",
+            EpochNotRetained = "The secrets of the message's epoch are not retained.",
+            StaleUpdatePath = "The update path doesn't change the HPKE key of the own leaf.",
+            TranscriptContinuityError = "The interim transcript hash doesn't continue the transcript of the current epoch.",
+            UnknownMember = "There is no member at the given leaf index.",
         }
         Complex {
             MlsCiphertextError(MlsCiphertextError) =
@@ -64,6 +70,8 @@ implement_error! {
                 "See [`FramingValidationError`](crate::group::FramingValidationError) for details.",
             ProposalValidationError(ProposalValidationError) =
                 "See [`ProposalValidationError`](crate::group::ProposalValidationError) for details.",
+            SecretTreeError(SecretTreeError) =
+                "See [`SecretTreeError`](crate::tree::secret_tree::SecretTreeError) for details.",
         }
     }
 }
@@ -77,20 +85,34 @@ implement_error! {
                 "No joiner secret found in the Welcome message.",
             MissingRatchetTree =
                 "No ratchet tree available to build initial tree after receiving a Welcome message.",
+            RatchetTreeRequiredButMissing =
+                "The group requires the ratchet tree extension in the Welcome message, but it is missing and no ratchet tree was provided.",
             TreeHashMismatch =
                 "The computed tree hash does not match the one in the GroupInfo.",
             ConfirmationTagMismatch =
                 "The computed confirmation tag does not match the expected one.",
             InvalidGroupInfoSignature =
                 "The signature on the GroupInfo is not valid.",
+            MalformedSignature =
+                "The signature on the GroupInfo doesn't have a valid length for the signer's signature scheme.",
+            SignerNotALeaf =
+                "The signer index of the GroupInfo doesn't refer to a leaf in the tree.",
             GroupInfoDecryptionFailure =
                 "Unable to decrypt the GroupInfo.",
+            MalformedGroupInfo =
+                "The tree hash or confirmed transcript hash in the GroupInfo doesn't have the length of the ciphersuite's hash.",
+            MalformedGroupSecrets =
+                "The joiner secret or path secret in the GroupSecrets doesn't have the length of the ciphersuite's hash.",
             DuplicateRatchetTreeExtension =
                 "Found a duplicate ratchet tree extension in the Welcome message.",
             UnsupportedMlsVersion =
                 "The Welcome message uses an unsupported MLS version.",
             MissingKeyPackage =
                 "The sender key package is missing.",
+            InvalidKeyPackage =
+                "The key package of the GroupInfo signer is invalid.",
+            KeyStoreError =
+                "Error performing key store operation.",
             UnknownError =
                 "An unknown error occurred.",
             }
@@ -146,6 +168,10 @@ implement_error! {
                 "Missing own key to apply proposal.",
             InitSecretNotFound =
                 "Missing init secret to apply proposal.",
+            ExternalCommitNotSupported =
+                "External commits are not supported yet.",
+            UnsupportedProposalType =
+                "The Commit contains a proposal of an unsupported type.",
         }
         Complex {
             PlaintextSignatureFailure(VerificationError) =
@@ -209,6 +235,7 @@ implement_error! {
         Simple {
             ProposalNotFound = "Not all proposals in the Commit were found locally.",
             SelfRemoval = "The sender of a Commit tried to remove themselves.",
+            UnsupportedProposalType = "The Commit contains a proposal of an unsupported type.",
         }
         Complex {
             NotAProposal(StagedProposalError) = "The given MLS Plaintext was not a Proposal.",
@@ -253,5 +280,10 @@ implement_error! {
         ExistingPublicKeyUpdateProposal = "HPKE public key of the update proposal already existed in tree.",
         DuplicateMemberRemoval = "Duplicate remove proposals for the same member.",
         UnknownMemberRemoval = "The remove proposal referenced a non-existing member.",
+        UnauthorizedExternalProposal = "The proposal was sent by an external sender that is not authorized by the group.",
+        UpdateSenderMismatch = "The update proposal was not sent by the member whose leaf it updates.",
+        WouldEmptyGroup = "Applying the proposals would leave no members in the group.",
+        UnsupportedCapabilitiesAddProposal = "The key package of the add proposal doesn't support the required capabilities of the group.",
+        ExpiredKeyPackageAddProposal = "The lifetime of the key package of the add proposal is not valid.",
     }
 }
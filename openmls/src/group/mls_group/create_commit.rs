@@ -65,6 +65,13 @@ impl MlsGroup {
                 apply_proposals_values.exclusion_list(),
                 backend,
             )?;
+            // An update path that keeps the leaf's HPKE key provides no
+            // forward secrecy.
+            if key_package_bundle.key_package().hpke_init_key()
+                == self.tree().own_key_package().hpke_init_key()
+            {
+                return Err(MlsGroupError::StaleUpdatePath);
+            }
             (Some(path), Some(key_package_bundle))
         } else {
             // If path is not needed, return empty commit secret
@@ -102,6 +109,7 @@ impl MlsGroup {
         )?;
 
         // Calculate tree hash
+        provisional_tree.canonicalize();
         let tree_hash = provisional_tree.tree_hash(backend);
 
         // Calculate group context
@@ -245,7 +253,9 @@ pub(crate) struct PlaintextSecret {
 
 impl PlaintextSecret {
     /// Prepare the `GroupSecrets` for a number of `invited_members` based on a
-    /// provisional `RatchetTree`. If there are `path_secrets` in the
+    /// provisional `RatchetTree`. The `invited_members` are the members added
+    /// by the commit, so the resulting Welcome contains exactly one entry per
+    /// new leaf and none for existing members. If there are `path_secrets` in the
     /// provisional tree, we need to include a `path_secret` into the
     /// `GroupSecrets`.
     pub(crate) fn new(
@@ -0,0 +1,143 @@
+//! # Message size estimates
+//!
+//! Before creating a commit, a client can estimate how large the resulting
+//! messages will be, e.g. to let the delivery service decide whether the
+//! proposals should be split across several commits. The estimates are based
+//! on the number of proposals, the shape of the tree and the ciphersuite and
+//! don't perform any cryptographic operations.
+
+use super::*;
+
+use super::proposals::ProposalStore;
+use crate::extensions::{ExtensionType, RatchetTreeExtension};
+use openmls_traits::types::SignatureScheme;
+use tls_codec::Size;
+
+/// Length of the authentication tag of all AEADs supported by MLS.
+const AEAD_TAG_LENGTH: usize = 16;
+
+impl MlsGroup {
+    /// Estimate the serialized size in bytes of the commit that
+    /// [`MlsGroup::create_commit()`] would create for the proposals in the
+    /// `proposal_store`. The estimate assumes that all proposals are
+    /// committed by reference and that the commit is sent as plaintext
+    /// without additional authenticated data.
+    pub fn estimate_commit_size(
+        &self,
+        proposal_store: &ProposalStore,
+        force_self_update: bool,
+    ) -> usize {
+        let hash_length = self.ciphersuite.hash_length();
+        let tree = self.tree();
+        let own_key_package = tree.own_key_package();
+
+        // Framing: wire format, group ID, epoch, sender, authenticated data,
+        // content type
+        let mut size = 1 + self.group_id().tls_serialized_len() + 8 + 5 + 4 + 1;
+
+        // Proposals by reference
+        size += 4;
+        let mut path_required = force_self_update;
+        for staged_proposal in proposal_store.proposals() {
+            size += 1 + 1 + hash_length;
+            path_required |= !staged_proposal.proposal().is_type(ProposalType::Add);
+        }
+
+        // Update path
+        size += 1;
+        if path_required {
+            let public_key_length = own_key_package.hpke_init_key().as_slice().len();
+            // The key package gets a parent hash extension.
+            size += own_key_package.tls_serialized_len();
+            if own_key_package
+                .extension_with_type(ExtensionType::ParentHash)
+                .is_none()
+            {
+                size += 2 + 4 + 1 + hash_length;
+            }
+            size += 4;
+            for resolution_size in tree.copath_resolution_sizes() {
+                let ciphertext_size = 2 + public_key_length + 2 + 1 + hash_length + AEAD_TAG_LENGTH;
+                size += 2 + public_key_length + 4 + resolution_size * ciphertext_size;
+            }
+        }
+
+        // Signature, confirmation tag and membership tag
+        size += signature_size(own_key_package.credential().signature_scheme());
+        size += 2 * (1 + 1 + hash_length);
+
+        size
+    }
+
+    /// Estimate the serialized size in bytes of the Welcome message that
+    /// [`MlsGroup::create_commit()`] would create for the Add proposals in the
+    /// `proposal_store`. Returns 0 if there are no Add proposals, because no
+    /// Welcome message is created in that case.
+    pub fn estimate_welcome_size(&self, proposal_store: &ProposalStore) -> usize {
+        let hash_length = self.ciphersuite.hash_length();
+        let tree = self.tree();
+        let own_key_package = tree.own_key_package();
+
+        let added_key_packages: Vec<&KeyPackage> = proposal_store
+            .proposals()
+            .filter_map(|staged_proposal| match staged_proposal.proposal() {
+                Proposal::Add(add_proposal) => Some(add_proposal.key_package()),
+                _ => None,
+            })
+            .collect();
+        if added_key_packages.is_empty() {
+            return 0;
+        }
+
+        // Group secrets: joiner secret, optional path secret and PSKs
+        let group_secrets_size = (1 + hash_length) + (1 + 1 + hash_length) + 2;
+        let public_key_length = own_key_package.hpke_init_key().as_slice().len();
+        let encrypted_group_secrets_size = (1 + hash_length)
+            + (2 + public_key_length)
+            + (2 + group_secrets_size + AEAD_TAG_LENGTH);
+
+        // Group info: group ID, epoch, tree hash, confirmed transcript hash,
+        // extensions, confirmation tag, signer index and signature
+        let mut group_info_size = self.group_id().tls_serialized_len()
+            + 8
+            + 3 * (1 + hash_length)
+            + 4
+            + self
+                .group_context_extensions()
+                .iter()
+                .map(|extension| extension.tls_serialized_len())
+                .sum::<usize>()
+            + 4
+            + 4
+            + signature_size(own_key_package.credential().signature_scheme());
+        if self.use_ratchet_tree_extension {
+            let mut nodes = tree.public_key_tree_copy();
+            // Each new member is added as a leaf, plus a blank parent.
+            for key_package in added_key_packages.iter() {
+                nodes.push(None);
+                nodes.push(Some(Node::new_leaf(Some((*key_package).clone()))));
+            }
+            group_info_size +=
+                Extension::RatchetTree(RatchetTreeExtension::new(nodes)).tls_serialized_len();
+        }
+
+        // Welcome: version, ciphersuite, secrets and encrypted group info
+        1 + 2
+            + 4
+            + added_key_packages.len() * encrypted_group_secrets_size
+            + 4
+            + group_info_size
+            + AEAD_TAG_LENGTH
+    }
+}
+
+/// The maximum serialized size of a signature with the given scheme.
+fn signature_size(signature_scheme: SignatureScheme) -> usize {
+    let length = match signature_scheme {
+        SignatureScheme::ED25519 => 64,
+        SignatureScheme::ED448 => 114,
+        SignatureScheme::ECDSA_SECP256R1_SHA256 => 72,
+        SignatureScheme::ECDSA_SECP521R1_SHA512 => 139,
+    };
+    2 + length
+}
@@ -3,9 +3,13 @@ use psk::{PreSharedKeys, PskSecret};
 
 pub mod create_commit;
 pub mod create_commit_params;
+mod estimate;
 mod new_from_welcome;
+mod past_epochs;
+pub mod policy;
 pub mod process;
 pub mod proposals;
+pub mod public_view;
 pub mod staged_commit;
 #[cfg(test)]
 mod test_create_commit_params;
@@ -14,7 +18,11 @@ mod test_duplicate_extension;
 #[cfg(test)]
 mod test_mls_group;
 #[cfg(test)]
+mod test_policy;
+#[cfg(test)]
 mod test_proposals;
+#[cfg(test)]
+mod test_welcome_signature;
 pub mod validation;
 
 use crate::ciphersuite::signable::{Signable, Verifiable};
@@ -23,6 +31,7 @@ use crate::credentials::{CredentialBundle, CredentialError};
 use crate::framing::*;
 use crate::group::*;
 use crate::key_packages::*;
+use crate::messages::external_join_package::ExternalJoinPackage;
 use crate::messages::public_group_state::{PublicGroupState, PublicGroupStateTbs};
 use crate::messages::{proposals::*, *};
 use crate::schedule::*;
@@ -41,6 +50,8 @@ use std::io::{Error, Read, Write};
 use std::cell::RefMut;
 use tls_codec::Serialize as TlsSerializeTrait;
 
+use past_epochs::PastEpochs;
+
 use super::errors::{
     ExporterError, FramingValidationError, MlsGroupError, ProposalValidationError, PskError,
 };
@@ -63,6 +74,14 @@ pub struct MlsGroup {
     use_ratchet_tree_extension: bool,
     // The MLS protocol version used in this group.
     mls_version: ProtocolVersion,
+    // Secrets of past epochs that are retained to decrypt late messages.
+    past_epochs: PastEpochs,
+    // How to handle committed proposals of unsupported types.
+    unknown_proposal_policy: UnknownProposalPolicy,
+    // Called whenever the group enters a new epoch. This is not persisted.
+    epoch_change_hook: EpochChangeHook,
+    // The confirmation tag of the current epoch.
+    confirmation_tag: ConfirmationTag,
 }
 
 implement_persistence!(
@@ -73,9 +92,58 @@ implement_persistence!(
     tree,
     interim_transcript_hash,
     use_ratchet_tree_extension,
-    mls_version
+    mls_version,
+    past_epochs,
+    unknown_proposal_policy,
+    epoch_change_hook,
+    confirmation_tag
 );
 
+/// Wrapper around the optional [`EpochChangeCallback`] of an [`MlsGroup`].
+/// Callbacks can't be persisted, so the hook is serialized as unit and is
+/// empty after loading a group.
+#[derive(Clone, Default)]
+pub(crate) struct EpochChangeHook(Option<EpochChangeCallback>);
+
+impl EpochChangeHook {
+    fn call(&self, group: &MlsGroup) {
+        if let Some(callback) = &self.0 {
+            callback(group)
+        }
+    }
+}
+
+impl std::fmt::Debug for EpochChangeHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "EpochChangeHook")
+    }
+}
+
+impl PartialEq for EpochChangeHook {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Serialize for EpochChangeHook {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_unit()
+    }
+}
+
+impl<'de> Deserialize<'de> for EpochChangeHook {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        <()>::deserialize(deserializer)?;
+        Ok(Self::default())
+    }
+}
+
 /// Builder for [`MlsGroup`].
 pub struct MlsGroupBuilder {
     key_package_bundle: KeyPackageBundle,
@@ -165,6 +233,9 @@ impl MlsGroupBuilder {
             .encryption_secret()
             .create_secret_tree(LeafIndex::from(1u32));
         let interim_transcript_hash = vec![];
+        let confirmation_tag = epoch_secrets
+            .confirmation_key()
+            .tag(backend, group_context.confirmed_transcript_hash.as_slice());
 
         Ok(MlsGroup {
             ciphersuite,
@@ -175,6 +246,10 @@ impl MlsGroupBuilder {
             interim_transcript_hash,
             use_ratchet_tree_extension: config.add_ratchet_tree_extension,
             mls_version: version,
+            past_epochs: PastEpochs::new(config.additional_as_epochs as usize),
+            unknown_proposal_policy: config.unknown_proposal_policy,
+            epoch_change_hook: EpochChangeHook(config.on_epoch_change),
+            confirmation_tag,
         })
     }
 }
@@ -199,10 +274,39 @@ impl MlsGroup {
             nodes_option,
             kpb,
             psk_fetcher_option,
+            &MlsGroupConfig::default(),
             backend,
         )?)
     }
 
+    /// Join a group from a welcome message like [`MlsGroup::new_from_welcome()`],
+    /// but apply the given `config` to the new group. The ratchet tree
+    /// extension setting is taken from the `Welcome`. The
+    /// [`MlsGroupConfig::on_epoch_change`] callback is invoked with the new
+    /// group before it is returned.
+    pub fn new_from_welcome_with_config(
+        welcome: Welcome,
+        nodes_option: Option<Vec<Option<Node>>>,
+        kpb: KeyPackageBundle,
+        psk_fetcher_option: Option<PskFetcher>,
+        config: MlsGroupConfig,
+        backend: &impl OpenMlsCryptoProvider,
+    ) -> Result<Self, MlsGroupError> {
+        let mut group = Self::new_from_welcome_internal(
+            welcome,
+            nodes_option,
+            kpb,
+            psk_fetcher_option,
+            &config,
+            backend,
+        )?;
+        group.past_epochs = PastEpochs::new(config.additional_as_epochs as usize);
+        group.unknown_proposal_policy = config.unknown_proposal_policy;
+        group.epoch_change_hook = EpochChangeHook(config.on_epoch_change);
+        group.epoch_change_hook.call(&group);
+        Ok(group)
+    }
+
     // === Create handshake messages ===
     // TODO: share functionality between these.
 
@@ -395,17 +499,86 @@ impl MlsGroup {
     }
 
     /// Decrypt an MlsCiphertext into an MlsPlaintext
+    ///
+    /// Ciphertexts from a past epoch are decrypted with the retained secrets
+    /// of that epoch. Returns [`MlsGroupError::EpochNotRetained`] if the
+    /// secrets of the ciphertext's epoch are not available.
     pub fn decrypt(
         &mut self,
         mls_ciphertext: &MlsCiphertext,
         backend: &impl OpenMlsCryptoProvider,
     ) -> Result<VerifiableMlsPlaintext, MlsGroupError> {
-        Ok(mls_ciphertext.to_plaintext(
-            self.ciphersuite(),
-            backend,
-            &self.epoch_secrets,
-            &mut self.secret_tree.borrow_mut(),
-        )?)
+        if mls_ciphertext.epoch() == self.context().epoch() {
+            return Ok(mls_ciphertext.to_plaintext(
+                self.ciphersuite(),
+                backend,
+                &self.epoch_secrets,
+                &mut self.secret_tree.borrow_mut(),
+            )?);
+        }
+        let ciphersuite = self.ciphersuite();
+        let (epoch_secrets, secret_tree) = self
+            .past_epochs
+            .get_mut(mls_ciphertext.epoch())
+            .ok_or(MlsGroupError::EpochNotRetained)?;
+        Ok(mls_ciphertext.to_plaintext(ciphersuite, backend, epoch_secrets, secret_tree)?)
+    }
+
+    /// Set the number of past epochs for which the secrets are retained to
+    /// decrypt late messages. The oldest epochs are dropped if more epochs are
+    /// retained already.
+    pub fn set_max_past_epochs(&mut self, max_past_epochs: usize) {
+        self.past_epochs.set_max_past_epochs(max_past_epochs);
+    }
+
+    /// Set how committed proposals of unsupported types are handled when
+    /// staging a commit. Groups joined with [`MlsGroup::new_from_welcome()`]
+    /// use the default [`UnknownProposalPolicy::Reject`].
+    pub fn set_unknown_proposal_policy(&mut self, unknown_proposal_policy: UnknownProposalPolicy) {
+        self.unknown_proposal_policy = unknown_proposal_policy;
+    }
+
+    /// Set the callback that is invoked at the end of
+    /// [`MlsGroup::merge_commit()`]. Replaces any previously set callback.
+    pub fn set_epoch_change_callback(&mut self, on_epoch_change: Option<EpochChangeCallback>) {
+        self.epoch_change_hook = EpochChangeHook(on_epoch_change);
+    }
+
+    /// Derive the application secrets of the member at `sender` for all
+    /// generations up to `up_to` in the current epoch, so that a burst of
+    /// out-of-order application messages from that member can be decrypted
+    /// without deriving secrets for each message.
+    pub fn prepare_generations(
+        &self,
+        sender: LeafIndex,
+        up_to: u32,
+        backend: &impl OpenMlsCryptoProvider,
+    ) -> Result<(), MlsGroupError> {
+        Ok(self
+            .secret_tree_mut()
+            .prepare_generations(self.ciphersuite, backend, sender, up_to)?)
+    }
+
+    /// Take a snapshot of the ratchet state of the current epoch's secret
+    /// tree. Persisting the snapshot after processing messages is cheaper than
+    /// persisting the whole group.
+    pub fn secret_tree_snapshot(&self) -> SecretTreeSnapshot {
+        self.secret_tree.borrow().snapshot()
+    }
+
+    /// Restore the ratchet state of the current epoch's secret tree from a
+    /// `snapshot` taken with [`MlsGroup::secret_tree_snapshot()`]. Returns an
+    /// error if the snapshot was taken from a secret tree of a different size.
+    pub fn restore_secret_tree(
+        &mut self,
+        snapshot: SecretTreeSnapshot,
+    ) -> Result<(), MlsGroupError> {
+        let secret_tree = self.secret_tree.get_mut();
+        if snapshot.size() != secret_tree.size() {
+            return Err(SecretTreeError::SnapshotSizeMismatch.into());
+        }
+        *secret_tree = SecretTree::restore(snapshot);
+        Ok(())
     }
 
     /// Set the context of the [`VerifiableMlsPlaintext`] (if it has not been
@@ -476,6 +649,60 @@ impl MlsGroup {
         self.epoch_secrets().authentication_secret().export()
     }
 
+    /// Returns the confirmation tag of the current epoch.
+    /// The tag is a MAC over the confirmed transcript hash and doesn't reveal
+    /// the confirmation key. It can therefore be published, e.g. to let the
+    /// delivery service check that members agree on the current epoch.
+    pub fn confirmation_tag(&self) -> &ConfirmationTag {
+        &self.confirmation_tag
+    }
+
+    /// Returns a safety number with `digits` decimal digits that members can
+    /// compare out of band, similar to the safety numbers used by Signal.
+    /// The number is derived from the exporter secret of the current epoch,
+    /// so all members in the same epoch get the same number.
+    pub fn safety_number(
+        &self,
+        backend: &impl OpenMlsCryptoProvider,
+        digits: usize,
+    ) -> Result<String, MlsGroupError> {
+        // Every chunk of 5 bytes is encoded as 5 decimal digits.
+        let chunks = digits.div_ceil(5);
+        let fingerprint = self.export_secret(backend, "safety number", &[], chunks * 5)?;
+        let mut safety_number = String::with_capacity(chunks * 5);
+        for chunk in fingerprint.chunks(5) {
+            let value = chunk
+                .iter()
+                .fold(0u64, |acc, &byte| (acc << 8) | u64::from(byte));
+            safety_number.push_str(&format!("{:05}", value % 100_000));
+        }
+        safety_number.truncate(digits);
+        Ok(safety_number)
+    }
+
+    /// Returns the tree hash of the current ratchet tree. This is the tree
+    /// hash in the group context of the current epoch, so two members that
+    /// are in sync get the same value. Comparing it is a cheaper divergence
+    /// check than the safety number.
+    pub fn tree_hash(&self, backend: &impl OpenMlsCryptoProvider) -> Vec<u8> {
+        self.tree().tree_hash(backend)
+    }
+
+    /// Returns the HPKE public key in the leaf of the member at `index`,
+    /// e.g. to encrypt a message to that member outside of the group. Note
+    /// that this is not the member's signature key and that it changes
+    /// whenever the member updates its leaf. Returns
+    /// [`MlsGroupError::UnknownMember`] if the leaf at `index` is blank or
+    /// outside of the tree.
+    pub fn member_hpke_key(&self, index: LeafIndex) -> Result<HpkePublicKey, MlsGroupError> {
+        let tree = self.tree();
+        tree.nodes
+            .get(NodeIndex::from(index).as_usize())
+            .and_then(|node| node.key_package())
+            .map(|key_package| key_package.hpke_init_key().clone())
+            .ok_or(MlsGroupError::UnknownMember)
+    }
+
     /// Loads the state from persisted state
     pub fn load<R: Read>(reader: R) -> Result<MlsGroup, Error> {
         serde_json::from_reader(reader).map_err(|e| e.into())
@@ -517,6 +744,8 @@ impl MlsGroup {
     }
 
     /// Get the group context extensions.
+    /// Members that are in sync report identical extensions, which makes this
+    /// useful to detect diverging group states.
     pub fn group_context_extensions(&self) -> &[Extension] {
         self.group_context.extensions()
     }
@@ -536,6 +765,21 @@ impl MlsGroup {
         pgs_tbs.sign(backend, credential_bundle)
     }
 
+    /// Export an [`ExternalJoinPackage`] with the signed `PublicGroupState`
+    /// and the ratchet tree of the group, which together hold everything a
+    /// new member needs to join the group with an external commit.
+    pub fn export_external_join_package(
+        &self,
+        credential_bundle: &CredentialBundle,
+        backend: &impl OpenMlsCryptoProvider,
+    ) -> Result<ExternalJoinPackage, CredentialError> {
+        let public_group_state = self.export_public_group_state(backend, credential_bundle)?;
+        Ok(ExternalJoinPackage::new(
+            public_group_state.into(),
+            self.tree().public_key_tree_copy(),
+        ))
+    }
+
     /// Returns `true` if the group uses the ratchet tree extension anf `false
     /// otherwise
     pub fn use_ratchet_tree_extension(&self) -> bool {
@@ -1,11 +1,13 @@
 use log::debug;
 use openmls_traits::crypto::OpenMlsCrypto;
+use openmls_traits::key_store::OpenMlsKeyStore;
 use tls_codec::Deserialize;
 
 use crate::ciphersuite::signable::Verifiable;
 use crate::extensions::ExtensionType;
 use crate::group::{mls_group::*, *};
 use crate::key_packages::*;
+use crate::key_store::ConsumedKeyPackage;
 use crate::messages::*;
 use crate::schedule::*;
 use crate::tree::{index::*, node::*, treemath, *};
@@ -16,6 +18,7 @@ impl MlsGroup {
         nodes_option: Option<Vec<Option<Node>>>,
         key_package_bundle: KeyPackageBundle,
         psk_fetcher_option: Option<PskFetcher>,
+        config: &MlsGroupConfig,
         backend: &impl OpenMlsCryptoProvider,
     ) -> Result<Self, WelcomeError> {
         log::debug!("MlsGroup::new_from_welcome_internal");
@@ -24,61 +27,43 @@ impl MlsGroup {
             return Err(WelcomeError::UnsupportedMlsVersion);
         }
 
-        let ciphersuite_name = welcome.ciphersuite();
-        let ciphersuite = Config::ciphersuite(ciphersuite_name)?;
+        let ciphersuite = Config::ciphersuite(welcome.ciphersuite())?;
 
-        // Find key_package in welcome secrets
-        let egs = if let Some(egs) = Self::find_key_package_from_welcome_secrets(
-            key_package_bundle.key_package(),
-            welcome.secrets(),
+        // Decrypt the group secrets with our key package
+        let group_secrets = Self::decrypt_group_secrets(
+            ciphersuite,
+            mls_version,
+            &welcome,
+            &key_package_bundle,
             backend,
-        ) {
-            egs
-        } else {
-            return Err(WelcomeError::JoinerSecretNotFound);
-        };
-        if ciphersuite_name != key_package_bundle.key_package().ciphersuite_name() {
-            let e = WelcomeError::CiphersuiteMismatch;
-            debug!("new_from_welcome {:?}", e);
-            return Err(e);
-        }
-
-        let group_secrets_bytes = backend
-            .crypto()
-            .hpke_open(
-                ciphersuite.hpke_config(),
-                &egs.encrypted_group_secrets,
-                key_package_bundle.private_key().as_slice(),
-                &[],
-                &[],
+        )?;
+        // The private key of the key package has now been used. Record this,
+        // so that the client can tell that the bundle shouldn't be used again.
+        backend
+            .key_store()
+            .store(
+                &ConsumedKeyPackage::key(&key_package_bundle.key_package().hash(backend)),
+                &ConsumedKeyPackage,
             )
-            .map_err(|_| CryptoError::HpkeDecryptionError)?;
-        let group_secrets = GroupSecrets::tls_deserialize(&mut group_secrets_bytes.as_slice())?
-            .config(ciphersuite, mls_version);
-        let joiner_secret = group_secrets.joiner_secret;
+            .map_err(|_| WelcomeError::KeyStoreError)?;
 
-        // Create key schedule
-        let mut key_schedule = KeySchedule::init(
+        // Create key schedule and decrypt the GroupInfo
+        let (mut key_schedule, group_info) = Self::decrypt_group_info(
             ciphersuite,
+            &welcome,
+            group_secrets.joiner_secret,
+            &group_secrets.psks,
+            psk_fetcher_option,
             backend,
-            joiner_secret,
-            psk_output(
-                ciphersuite,
-                backend,
-                psk_fetcher_option,
-                &group_secrets.psks,
-            )?,
-        );
-
-        // Derive welcome key & nonce from the key schedule
-        let (welcome_key, welcome_nonce) = key_schedule
-            .welcome(backend)?
-            .derive_welcome_key_nonce(backend);
+        )?;
 
-        let group_info_bytes = welcome_key
-            .aead_open(backend, welcome.encrypted_group_info(), &[], &welcome_nonce)
-            .map_err(|_| WelcomeError::GroupInfoDecryptionFailure)?;
-        let group_info = GroupInfo::tls_deserialize(&mut group_info_bytes.as_slice())?;
+        // The hashes in the GroupInfo end up in the group context, so they
+        // must have the length of the ciphersuite's hash.
+        if group_info.tree_hash().len() != ciphersuite.hash_length()
+            || group_info.confirmed_transcript_hash().len() != ciphersuite.hash_length()
+        {
+            return Err(WelcomeError::MalformedGroupInfo);
+        }
 
         // Make sure that we can support the required capabilities in the group info.
         let group_context_extensions = group_info.group_context_extensions();
@@ -131,13 +116,22 @@ impl MlsGroup {
             Some(tree) => (tree.as_slice(), true),
             None => match nodes_option.as_ref() {
                 Some(n) => (n.as_slice(), false),
+                None if config.require_tree_in_welcome => {
+                    return Err(WelcomeError::RatchetTreeRequiredButMissing)
+                }
                 None => return Err(WelcomeError::MissingRatchetTree),
             },
         };
 
-        let mut tree = RatchetTree::new_from_nodes(backend, key_package_bundle, nodes)?;
+        let mut tree = RatchetTree::new_from_nodes_with_max_depth(
+            backend,
+            key_package_bundle,
+            nodes,
+            config.max_tree_depth as usize,
+        )?;
 
         // Verify tree hash
+        tree.canonicalize();
         let tree_hash = tree.tree_hash(backend);
         if tree_hash != group_info.tree_hash() {
             return Err(WelcomeError::TreeHashMismatch);
@@ -146,11 +140,32 @@ impl MlsGroup {
         // Verify parent hashes
         tree.verify_parent_hashes(backend)?;
 
+        // The signer index must refer to a leaf within the tree. Since it is a
+        // `LeafIndex`, it always maps to an even node index, but it can still
+        // point past the end of the tree.
+        let signer_index = group_info.signer_index();
+        if signer_index >= tree.leaf_count() || !tree.nodes[signer_index].node_type.is_leaf() {
+            return Err(WelcomeError::SignerNotALeaf);
+        }
+
         // Verify GroupInfo signature
-        let signer_node = tree.nodes[group_info.signer_index()].clone();
+        let signer_node = tree.nodes[signer_index].clone();
         let signer_key_package = signer_node
             .key_package
             .ok_or(WelcomeError::MissingKeyPackage)?;
+        // A malformed key package of the signer is rejected before its
+        // credential is used.
+        signer_key_package
+            .check_extension_uniqueness()
+            .map_err(|_| WelcomeError::InvalidKeyPackage)?;
+        // A signature that can't have been produced with the signer's
+        // signature scheme is rejected before verifying it.
+        if !group_info
+            .signature()
+            .has_valid_length(signer_key_package.credential().signature_scheme())
+        {
+            return Err(WelcomeError::MalformedSignature);
+        }
         group_info
             .verify_no_out(backend, signer_key_package.credential())
             .map_err(|_| WelcomeError::InvalidGroupInfoSignature)?;
@@ -195,6 +210,9 @@ impl MlsGroup {
         key_schedule.add_context(backend, &group_context)?;
         let epoch_secrets = key_schedule.epoch_secrets(backend, true)?;
 
+        // The secret tree must have the same size as the one the committer
+        // created from its provisional tree. This holds because the tree hash
+        // verified above covers all nodes, including trailing blank leaves.
         let secret_tree = epoch_secrets
             .encryption_secret()
             .create_secret_tree(tree.leaf_count());
@@ -225,12 +243,118 @@ impl MlsGroup {
                 interim_transcript_hash,
                 use_ratchet_tree_extension: enable_ratchet_tree_extension,
                 mls_version,
+                past_epochs: PastEpochs::default(),
+                unknown_proposal_policy: UnknownProposalPolicy::default(),
+                epoch_change_hook: EpochChangeHook::default(),
+                confirmation_tag,
             })
         }
     }
 
     // Helper functions
 
+    /// Find the encrypted group secrets for the key package of
+    /// `key_package_bundle` in the `welcome` and decrypt them.
+    fn decrypt_group_secrets(
+        ciphersuite: &'static Ciphersuite,
+        mls_version: ProtocolVersion,
+        welcome: &Welcome,
+        key_package_bundle: &KeyPackageBundle,
+        backend: &impl OpenMlsCryptoProvider,
+    ) -> Result<GroupSecrets, WelcomeError> {
+        // Find key_package in welcome secrets
+        let egs = if let Some(egs) = Self::find_key_package_from_welcome_secrets(
+            key_package_bundle.key_package(),
+            welcome.secrets(),
+            backend,
+        ) {
+            egs
+        } else {
+            return Err(WelcomeError::JoinerSecretNotFound);
+        };
+        if welcome.ciphersuite() != key_package_bundle.key_package().ciphersuite_name() {
+            let e = WelcomeError::CiphersuiteMismatch;
+            debug!("new_from_welcome {:?}", e);
+            return Err(e);
+        }
+
+        let group_secrets_bytes = backend
+            .crypto()
+            .hpke_open(
+                ciphersuite.hpke_config(),
+                &egs.encrypted_group_secrets,
+                key_package_bundle.private_key().as_slice(),
+                &[],
+                &[],
+            )
+            .map_err(|_| CryptoError::HpkeDecryptionError)?;
+        GroupSecrets::tls_deserialize(&mut group_secrets_bytes.as_slice())?
+            .config(ciphersuite, mls_version)
+    }
+
+    /// Initialize the key schedule from the `joiner_secret` and the `psks` and
+    /// use the welcome secret to decrypt the GroupInfo of the `welcome`.
+    fn decrypt_group_info(
+        ciphersuite: &'static Ciphersuite,
+        welcome: &Welcome,
+        joiner_secret: JoinerSecret,
+        psks: &PreSharedKeys,
+        psk_fetcher_option: Option<PskFetcher>,
+        backend: &impl OpenMlsCryptoProvider,
+    ) -> Result<(KeySchedule, GroupInfo), WelcomeError> {
+        let key_schedule = KeySchedule::init(
+            ciphersuite,
+            backend,
+            joiner_secret,
+            psk_output(ciphersuite, backend, psk_fetcher_option, psks)?,
+        );
+
+        // Derive welcome key & nonce from the key schedule
+        let (welcome_key, welcome_nonce) = key_schedule
+            .welcome(backend)?
+            .derive_welcome_key_nonce(backend);
+
+        let group_info_bytes = welcome_key
+            .aead_open(backend, welcome.encrypted_group_info(), &[], &welcome_nonce)
+            .map_err(|_| WelcomeError::GroupInfoDecryptionFailure)?;
+        let group_info = GroupInfo::tls_deserialize(&mut group_info_bytes.as_slice())?;
+        Ok((key_schedule, group_info))
+    }
+
+    /// Decrypt the GroupInfo of the `welcome` with `key_package_bundle`
+    /// without joining the group. The key package is not marked as consumed
+    /// and neither the ratchet tree nor the signature on the GroupInfo are
+    /// verified.
+    pub(crate) fn inspect_welcome(
+        welcome: &Welcome,
+        key_package_bundle: &KeyPackageBundle,
+        psk_fetcher_option: Option<PskFetcher>,
+        backend: &impl OpenMlsCryptoProvider,
+    ) -> Result<GroupInfoView, WelcomeError> {
+        let mls_version = *welcome.version();
+        if !Config::supported_versions().contains(&mls_version) {
+            return Err(WelcomeError::UnsupportedMlsVersion);
+        }
+        let ciphersuite = Config::ciphersuite(welcome.ciphersuite())?;
+
+        let group_secrets = Self::decrypt_group_secrets(
+            ciphersuite,
+            mls_version,
+            welcome,
+            key_package_bundle,
+            backend,
+        )?;
+        let (_key_schedule, group_info) = Self::decrypt_group_info(
+            ciphersuite,
+            welcome,
+            group_secrets.joiner_secret,
+            &group_secrets.psks,
+            psk_fetcher_option,
+            backend,
+        )?;
+        Ok(GroupInfoView::from(group_info))
+    }
+
     pub(crate) fn find_key_package_from_welcome_secrets(
         key_package: &KeyPackage,
         welcome_secrets: &[EncryptedGroupSecrets],
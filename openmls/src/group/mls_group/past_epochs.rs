@@ -0,0 +1,76 @@
+//! # Past epoch secrets
+//!
+//! Application messages may arrive after the group has already moved on to a
+//! new epoch. To still be able to decrypt them, an [`MlsGroup`] can retain the
+//! [`EpochSecrets`] and [`SecretTree`] of a limited number of past epochs.
+
+use std::collections::VecDeque;
+
+use super::*;
+
+/// The secrets of a single past epoch.
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(test, derive(PartialEq))]
+struct PastEpoch {
+    epoch: GroupEpoch,
+    epoch_secrets: EpochSecrets,
+    secret_tree: SecretTree,
+}
+
+/// A bounded list of past epochs, ordered from oldest to newest.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(test, derive(PartialEq))]
+pub(crate) struct PastEpochs {
+    max_past_epochs: usize,
+    epochs: VecDeque<PastEpoch>,
+}
+
+impl PastEpochs {
+    /// Create an empty list that retains at most `max_past_epochs` epochs.
+    pub(crate) fn new(max_past_epochs: usize) -> Self {
+        Self {
+            max_past_epochs,
+            epochs: VecDeque::new(),
+        }
+    }
+
+    /// Change the number of retained epochs. Drops the oldest epochs if there
+    /// are more than `max_past_epochs` already.
+    pub(crate) fn set_max_past_epochs(&mut self, max_past_epochs: usize) {
+        self.max_past_epochs = max_past_epochs;
+        self.truncate();
+    }
+
+    /// Retain the secrets of the given `epoch`. The oldest epoch is dropped if
+    /// the retention window is full.
+    pub(crate) fn add(
+        &mut self,
+        epoch: GroupEpoch,
+        epoch_secrets: EpochSecrets,
+        secret_tree: SecretTree,
+    ) {
+        self.epochs.push_back(PastEpoch {
+            epoch,
+            epoch_secrets,
+            secret_tree,
+        });
+        self.truncate();
+    }
+
+    /// Get the secrets of the given `epoch` if it is retained.
+    pub(crate) fn get_mut(
+        &mut self,
+        epoch: GroupEpoch,
+    ) -> Option<(&EpochSecrets, &mut SecretTree)> {
+        self.epochs
+            .iter_mut()
+            .find(|past_epoch| past_epoch.epoch == epoch)
+            .map(|past_epoch| (&past_epoch.epoch_secrets, &mut past_epoch.secret_tree))
+    }
+
+    fn truncate(&mut self) {
+        while self.epochs.len() > self.max_past_epochs {
+            self.epochs.pop_front();
+        }
+    }
+}
@@ -0,0 +1,70 @@
+//! # Group policies
+//!
+//! A [`GroupPolicy`] describes rules a committer wants to enforce on the
+//! group, e.g. removing members whose key packages have expired.
+//! [`MlsGroup::policy_proposals()`] inspects the current tree and creates the
+//! proposals that are needed to satisfy the policy.
+
+use super::*;
+
+/// A set of rules that should hold for the members of a group.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct GroupPolicy {
+    /// Remove members whose key package lifetime is no longer valid.
+    pub remove_expired_members: bool,
+}
+
+impl MlsGroup {
+    /// Create the proposals a committer should include in order to satisfy
+    /// the given `policy`.
+    ///
+    /// The own leaf is never proposed for removal, because a member can't
+    /// commit its own removal.
+    pub fn policy_proposals(
+        &self,
+        policy: &GroupPolicy,
+        framing_parameters: FramingParameters,
+        credential_bundle: &CredentialBundle,
+        backend: &impl OpenMlsCryptoProvider,
+    ) -> Result<Vec<MlsPlaintext>, MlsGroupError> {
+        let mut proposals = Vec::new();
+
+        if policy.remove_expired_members {
+            for removed_index in self.expired_members() {
+                proposals.push(self.create_remove_proposal(
+                    framing_parameters,
+                    credential_bundle,
+                    removed_index,
+                    backend,
+                )?);
+            }
+        }
+
+        Ok(proposals)
+    }
+
+    /// Returns the leaf indices of all other members whose key package has no
+    /// valid lifetime extension.
+    fn expired_members(&self) -> Vec<LeafIndex> {
+        let tree = self.tree();
+        let own_index = tree.own_node_index();
+        let mut expired_members = Vec::new();
+        for index in 0..tree.leaf_count().as_usize() {
+            let leaf_index = LeafIndex::from(index);
+            if leaf_index == own_index {
+                continue;
+            }
+            if let Some(key_package) = tree.nodes[leaf_index].key_package() {
+                let lifetime_is_valid = key_package
+                    .extension_with_type(ExtensionType::Lifetime)
+                    .and_then(|extension| extension.as_lifetime_extension().ok())
+                    .map(|lifetime| lifetime.is_valid())
+                    .unwrap_or(false);
+                if !lifetime_is_valid {
+                    expired_members.push(leaf_index);
+                }
+            }
+        }
+        expired_members
+    }
+}
@@ -1,4 +1,5 @@
 use crate::group::errors::*;
+use crate::group::UnknownProposalPolicy;
 
 use crate::messages::proposals::{
     AddProposal, PreSharedKeyProposal, Proposal, ProposalOrRef, ProposalOrRefType,
@@ -128,6 +129,7 @@ impl StagedProposalQueue {
         committed_proposals: Vec<ProposalOrRef>,
         proposal_store: &ProposalStore,
         sender: Sender,
+        unknown_proposal_policy: UnknownProposalPolicy,
     ) -> Result<Self, StagedProposalQueueError> {
         // Feed the `proposals_by_reference` in a `HashMap` so that we can easily
         // extract then by reference later
@@ -178,6 +180,16 @@ impl StagedProposalQueue {
                     }
                 }
             };
+            // Proposals of unsupported types can't be applied. Depending on
+            // the policy, the commit is rejected or the proposal is skipped.
+            if !queued_proposal.proposal().proposal_type().is_supported() {
+                match unknown_proposal_policy {
+                    UnknownProposalPolicy::Reject => {
+                        return Err(StagedProposalQueueError::UnsupportedProposalType)
+                    }
+                    UnknownProposalPolicy::Ignore => continue,
+                }
+            }
             proposal_queue.add(queued_proposal);
         }
         Ok(proposal_queue)
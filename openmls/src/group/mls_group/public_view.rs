@@ -0,0 +1,76 @@
+//! # Public group views
+//!
+//! A [`PublicGroupView`] is a snapshot of the public state of an [`MlsGroup`]
+//! in a given epoch. Clients that keep such snapshots can compare them with
+//! [`MlsGroup::member_delta()`] to find out which members joined or left the
+//! group in between.
+
+use super::*;
+use crate::credentials::Credential;
+
+/// A snapshot of the public state of a group in a given epoch.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PublicGroupView {
+    group_id: GroupId,
+    epoch: GroupEpoch,
+    members: Vec<Credential>,
+}
+
+impl PublicGroupView {
+    /// Get the group ID.
+    pub fn group_id(&self) -> &GroupId {
+        &self.group_id
+    }
+
+    /// Get the epoch of this snapshot.
+    pub fn epoch(&self) -> GroupEpoch {
+        self.epoch
+    }
+
+    /// Get the credentials of all members in this snapshot.
+    pub fn members(&self) -> &[Credential] {
+        &self.members
+    }
+}
+
+/// The difference between the members of two [`PublicGroupView`]s.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MemberDelta {
+    /// Members that are only in the newer view.
+    pub joined: Vec<Credential>,
+    /// Members that are only in the older view.
+    pub left: Vec<Credential>,
+}
+
+impl MlsGroup {
+    /// Take a snapshot of the public state of the group in the current epoch.
+    pub fn public_group_view(&self) -> PublicGroupView {
+        PublicGroupView {
+            group_id: self.group_id().clone(),
+            epoch: self.context().epoch(),
+            members: self
+                .tree()
+                .key_packages()
+                .map(|key_package| key_package.credential().clone())
+                .collect(),
+        }
+    }
+
+    /// Compute which members joined and which members left the group between
+    /// the `old` and the `new` view. Members are compared by their credential.
+    pub fn member_delta(old: &PublicGroupView, new: &PublicGroupView) -> MemberDelta {
+        let joined = new
+            .members
+            .iter()
+            .filter(|credential| !old.members.contains(credential))
+            .cloned()
+            .collect();
+        let left = old
+            .members
+            .iter()
+            .filter(|credential| !new.members.contains(credential))
+            .cloned()
+            .collect();
+        MemberDelta { joined, left }
+    }
+}
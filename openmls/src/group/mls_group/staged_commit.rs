@@ -3,6 +3,8 @@ use super::proposals::{
     StagedUpdateProposal,
 };
 use super::*;
+use crate::group::errors::StagedProposalQueueError;
+use crate::tree::errors::ParentHashError;
 use core::fmt::Debug;
 
 impl MlsGroup {
@@ -15,6 +17,11 @@ impl MlsGroup {
     ///  - Verifies the confirmation tag/membership tag
     /// Returns a [StagedCommit] that can be inspected and later merged
     /// into the group state with [merge_commit()]
+    /// Returns `MlsPlaintextError::UnknownSender` if the sender of a member
+    /// commit is a blank or nonexistent leaf.
+    /// Returns `MlsGroupError::TranscriptContinuityError` if the interim
+    /// transcript hash of the group doesn't belong to the current epoch.
+    /// External commits are staged separately, see [`Commit::is_external()`].
     /// This function does the following checks:
     ///  - ValSem100
     ///  - ValSem101
@@ -25,6 +32,7 @@ impl MlsGroup {
     ///  - ValSem106
     ///  - ValSem107
     ///  - ValSem108
+    ///  - At least one member remains in the group
     ///  - ValSem109
     ///  - ValSem110
     ///  - ValSem201
@@ -49,6 +57,10 @@ impl MlsGroup {
             return Err(StageCommitError::EpochMismatch.into());
         }
 
+        // The interim transcript hash is the input to the new confirmed
+        // transcript hash, so it has to continue the current transcript.
+        self.verify_transcript_continuity(backend)?;
+
         // Extract Commit & Confirmation Tag from MlsPlaintext
         let commit = match mls_plaintext.content() {
             MlsPlaintextContentType::Commit(commit) => commit,
@@ -59,6 +71,23 @@ impl MlsGroup {
             .confirmation_tag()
             .ok_or(StageCommitError::ConfirmationTagMissing)?;
 
+        // The committer of an external commit is not a member of the group
+        // yet, so external commits need to go through the external join
+        // validation instead of the member checks below.
+        if commit.is_external() {
+            return self.stage_external_commit(commit);
+        }
+
+        // Make sure the sender is a non-blank leaf in the tree before we
+        // look up anything about it.
+        if mls_plaintext.sender().is_member() {
+            let sender_index = mls_plaintext.sender_index();
+            let tree = self.tree();
+            if sender_index >= tree.leaf_count() || tree.nodes[sender_index].is_blank() {
+                return Err(MlsPlaintextError::UnknownSender.into());
+            }
+        }
+
         // Build a queue with all proposals from the Commit and check that we have all
         // of the proposals by reference locally
         let proposal_queue = StagedProposalQueue::from_committed_proposals(
@@ -67,11 +96,20 @@ impl MlsGroup {
             commit.proposals.as_slice().to_vec(),
             proposal_store,
             *mls_plaintext.sender(),
+            self.unknown_proposal_policy,
         )
-        .map_err(|_| StageCommitError::MissingProposal)?;
+        .map_err(|e| match e {
+            StagedProposalQueueError::UnsupportedProposalType => {
+                StageCommitError::UnsupportedProposalType
+            }
+            _ => StageCommitError::MissingProposal,
+        })?;
 
         // Validate the staged proposals by doing the following checks:
 
+        // Only authorized external senders
+        self.validate_proposal_senders(&proposal_queue)?;
+
         // ValSem100
         // ValSem101
         // ValSem102
@@ -83,6 +121,9 @@ impl MlsGroup {
         // ValSem107
         // ValSem108
         self.validate_remove_proposals(&proposal_queue)?;
+        // At least one member has to remain in the group
+        self.validate_group_not_emptied(&proposal_queue)?;
+        // Update senders match the updated leaf
         // ValSem109
         // ValSem110
         self.validate_update_proposals(&proposal_queue)?;
@@ -171,6 +212,7 @@ impl MlsGroup {
             &self.interim_transcript_hash,
         )?;
 
+        provisional_tree.canonicalize();
         let provisional_group_context = GroupContext::new(
             self.group_context.group_id.clone(),
             provisional_epoch,
@@ -222,25 +264,17 @@ impl MlsGroup {
         }
 
         // Verify KeyPackage extensions
-        if let Some(path) = &commit.path {
-            if !is_own_commit {
-                let parent_hash = provisional_tree.set_parent_hashes(backend, sender);
-                if let Some(received_parent_hash) = path
-                    .leaf_key_package
-                    .extension_with_type(ExtensionType::ParentHash)
-                {
-                    let parent_hash_extension =
-                        match received_parent_hash.as_parent_hash_extension() {
-                            Ok(phe) => phe,
-                            Err(_) => return Err(StageCommitError::NoParentHashExtension.into()),
-                        };
-                    if parent_hash != parent_hash_extension.parent_hash() {
-                        return Err(StageCommitError::ParentHashMismatch.into());
+        if commit.path.is_some() && !is_own_commit {
+            // The parent hashes of the sender's direct path were set when
+            // applying the path, so the sender's leaf has to match them.
+            provisional_tree
+                .verify_leaf_parent_hash(backend, sender)
+                .map_err(|e| match e {
+                    ParentHashError::MissingLeafParentHash => {
+                        StageCommitError::NoParentHashExtension
                     }
-                } else {
-                    return Err(StageCommitError::NoParentHashExtension.into());
-                }
-            }
+                    _ => StageCommitError::ParentHashMismatch,
+                })?;
         }
 
         // Create a secret_tree, consuming the `encryption_secret` in the
@@ -256,15 +290,62 @@ impl MlsGroup {
             interim_transcript_hash,
             secret_tree: RefCell::new(secret_tree),
             original_nodes,
+            confirmation_tag: own_confirmation_tag,
         })
     }
 
-    /// Merges a [`StagedCommit`] into the group state.
+    /// Checks that the interim transcript hash of the group was derived from
+    /// the confirmed transcript hash and the confirmation tag of the current
+    /// epoch. Groups that were created from scratch start with an empty
+    /// interim transcript hash.
+    /// Returns `MlsGroupError::TranscriptContinuityError` otherwise.
+    fn verify_transcript_continuity(
+        &self,
+        backend: &impl OpenMlsCryptoProvider,
+    ) -> Result<(), MlsGroupError> {
+        let expected_interim_transcript_hash = if self.group_context.epoch == GroupEpoch(0) {
+            vec![]
+        } else {
+            update_interim_transcript_hash(
+                self.ciphersuite(),
+                backend,
+                &MlsPlaintextCommitAuthData::from(&self.confirmation_tag),
+                self.group_context.confirmed_transcript_hash.as_slice(),
+            )?
+        };
+        if self.interim_transcript_hash != expected_interim_transcript_hash {
+            log::error!("Interim transcript hash doesn't match the current epoch.");
+            return Err(MlsGroupError::TranscriptContinuityError);
+        }
+        Ok(())
+    }
+
+    /// Stages an external commit, i.e. a commit that adds the committer to the
+    /// group through an ExternalInit proposal.
+    ///
+    /// TODO: #556 Joining through external commits isn't implemented yet, so
+    /// external commits are rejected with
+    /// [`StageCommitError::ExternalCommitNotSupported`].
+    fn stage_external_commit(&self, _commit: &Commit) -> Result<StagedCommit, MlsGroupError> {
+        Err(StageCommitError::ExternalCommitNotSupported.into())
+    }
+
+    /// Merges a [`StagedCommit`] into the group state and invokes the epoch
+    /// change callback, if any, with the new state.
     pub fn merge_commit(&mut self, staged_commit: StagedCommit) {
+        let past_epoch = self.group_context.epoch;
+        let past_epoch_secrets =
+            std::mem::replace(&mut self.epoch_secrets, staged_commit.epoch_secrets);
+        let past_secret_tree = std::mem::replace(&mut self.secret_tree, staged_commit.secret_tree);
+        self.past_epochs.add(
+            past_epoch,
+            past_epoch_secrets,
+            past_secret_tree.into_inner(),
+        );
         self.group_context = staged_commit.group_context;
-        self.epoch_secrets = staged_commit.epoch_secrets;
         self.interim_transcript_hash = staged_commit.interim_transcript_hash;
-        self.secret_tree = staged_commit.secret_tree;
+        self.confirmation_tag = staged_commit.confirmation_tag;
+        self.epoch_change_hook.call(self);
     }
 
     /// This is temporary and will disappear when #424 is addressed.
@@ -285,6 +366,7 @@ pub struct StagedCommit {
     interim_transcript_hash: Vec<u8>,
     secret_tree: RefCell<SecretTree>,
     original_nodes: Vec<Node>,
+    confirmation_tag: ConfirmationTag,
 }
 
 impl StagedCommit {
@@ -105,7 +105,7 @@ ctest_ciphersuites!(duplicate_ratchet_tree_extension, test(ciphersuite_name: Cip
             &[],
             &[],
         ).expect("Could not decrypt group secrets");
-    let group_secrets = GroupSecrets::tls_deserialize(&mut group_secrets_bytes.as_slice()).expect("Could not decode GroupSecrets").config(ciphersuite, ProtocolVersion::default());
+    let group_secrets = GroupSecrets::tls_deserialize(&mut group_secrets_bytes.as_slice()).expect("Could not decode GroupSecrets").config(ciphersuite, ProtocolVersion::default()).expect("Malformed GroupSecrets");
     let joiner_secret = group_secrets.joiner_secret;
 
     // Create key schedule
@@ -1,14 +1,18 @@
 use openmls_rust_crypto::OpenMlsRustCrypto;
 use openmls_traits::{crypto::OpenMlsCrypto, types::HpkeCiphertext, OpenMlsCryptoProvider};
-use tls_codec::Serialize;
+use tls_codec::{Serialize, Size};
 
 use crate::{
     ciphersuite::{signable::Signable, AeadNonce},
-    group::{create_commit_params::CreateCommitParams, GroupEpoch},
-    messages::{Commit, ConfirmationTag, EncryptedGroupSecrets, GroupInfoPayload},
+    group::{create_commit_params::CreateCommitParams, public_view::MemberDelta, GroupEpoch},
+    messages::{
+        proposals::{ExternalInitProposal, Proposal, ProposalOrRef},
+        Commit, ConfirmationTag, EncryptedGroupSecrets, GroupInfoPayload,
+    },
     prelude::*,
     schedule::psk::*,
-    tree::{TreeError, UpdatePath, UpdatePathNode},
+    test_utils::setup_group_with_members,
+    tree::{node::Node, ParentHashError, TreeError, UpdatePath, UpdatePathNode},
 };
 
 #[test]
@@ -155,6 +159,7 @@ fn test_failed_groupinfo_decryption() {
                 None,
                 key_package_bundle,
                 None,
+                &MlsGroupConfig::default(),
                 &crypto,
             )
             .expect_err("Creation of MLS group from a broken Welcome was successful.");
@@ -547,3 +552,1636 @@ ctest_ciphersuites!(test_psks, test(ciphersuite_name: CiphersuiteName) {
         .unwrap();
 
 });
+
+#[test]
+fn test_member_delta() {
+    let crypto = OpenMlsRustCrypto::default();
+    let ciphersuite = &Config::supported_ciphersuites()[0];
+    let framing_parameters = FramingParameters::new(&[], WireFormat::MlsPlaintext);
+
+    let setup_client = |id: &str| {
+        let credential_bundle = CredentialBundle::new(
+            id.into(),
+            CredentialType::Basic,
+            ciphersuite.signature_scheme(),
+            &crypto,
+        )
+        .unwrap();
+        let key_package_bundle =
+            KeyPackageBundle::new(&[ciphersuite.name()], &credential_bundle, &crypto, vec![])
+                .unwrap();
+        (credential_bundle, key_package_bundle)
+    };
+    let (alice_credential_bundle, alice_key_package_bundle) = setup_client("Alice");
+    let (bob_credential_bundle, bob_key_package_bundle) = setup_client("Bob");
+    let (charlie_credential_bundle, charlie_key_package_bundle) = setup_client("Charlie");
+
+    let mut alice_group = MlsGroup::builder(GroupId::random(&crypto), alice_key_package_bundle)
+        .build(&crypto)
+        .expect("Error creating group.");
+
+    // Alice adds Bob
+    let bob_add_proposal = alice_group
+        .create_add_proposal(
+            framing_parameters,
+            &alice_credential_bundle,
+            bob_key_package_bundle.key_package().clone(),
+            &crypto,
+        )
+        .expect("Could not create proposal.");
+    let proposal_store = ProposalStore::from_staged_proposal(
+        StagedProposal::from_mls_plaintext(ciphersuite, &crypto, bob_add_proposal)
+            .expect("Could not create StagedProposal."),
+    );
+    let params = CreateCommitParams::builder()
+        .framing_parameters(framing_parameters)
+        .credential_bundle(&alice_credential_bundle)
+        .proposal_store(&proposal_store)
+        .force_self_update(false)
+        .build();
+    let (commit, _welcome_option, _kpb_option) = alice_group
+        .create_commit(params, &crypto)
+        .expect("Error creating commit");
+    let staged_commit = alice_group
+        .stage_commit(&commit, &proposal_store, &[], None, &crypto)
+        .expect("Error staging commit");
+    alice_group.merge_commit(staged_commit);
+
+    let old_view = alice_group.public_group_view();
+
+    // Alice adds Charlie and removes Bob in the same commit
+    let charlie_add_proposal = alice_group
+        .create_add_proposal(
+            framing_parameters,
+            &alice_credential_bundle,
+            charlie_key_package_bundle.key_package().clone(),
+            &crypto,
+        )
+        .expect("Could not create proposal.");
+    let bob_remove_proposal = alice_group
+        .create_remove_proposal(
+            framing_parameters,
+            &alice_credential_bundle,
+            LeafIndex::from(1u32),
+            &crypto,
+        )
+        .expect("Could not create proposal.");
+    let mut proposal_store = ProposalStore::from_staged_proposal(
+        StagedProposal::from_mls_plaintext(ciphersuite, &crypto, charlie_add_proposal)
+            .expect("Could not create StagedProposal."),
+    );
+    proposal_store.add(
+        StagedProposal::from_mls_plaintext(ciphersuite, &crypto, bob_remove_proposal)
+            .expect("Could not create StagedProposal."),
+    );
+    let params = CreateCommitParams::builder()
+        .framing_parameters(framing_parameters)
+        .credential_bundle(&alice_credential_bundle)
+        .proposal_store(&proposal_store)
+        .force_self_update(false)
+        .build();
+    let (commit, _welcome_option, _kpb_option) = alice_group
+        .create_commit(params, &crypto)
+        .expect("Error creating commit");
+    let staged_commit = alice_group
+        .stage_commit(&commit, &proposal_store, &[], None, &crypto)
+        .expect("Error staging commit");
+    alice_group.merge_commit(staged_commit);
+
+    let new_view = alice_group.public_group_view();
+    assert_eq!(new_view.epoch(), GroupEpoch(old_view.epoch().0 + 1));
+
+    let delta = MlsGroup::member_delta(&old_view, &new_view);
+    assert_eq!(
+        delta.joined,
+        vec![charlie_credential_bundle.credential().clone()]
+    );
+    assert_eq!(delta.left, vec![bob_credential_bundle.credential().clone()]);
+
+    // Comparing a view with itself yields an empty delta.
+    assert_eq!(
+        MlsGroup::member_delta(&new_view, &new_view),
+        MemberDelta::default()
+    );
+}
+
+#[test]
+fn test_safety_number() {
+    let crypto = OpenMlsRustCrypto::default();
+    let ciphersuite = &Config::supported_ciphersuites()[0];
+    let framing_parameters = FramingParameters::new(&[], WireFormat::MlsPlaintext);
+
+    let alice_credential_bundle = CredentialBundle::new(
+        "Alice".into(),
+        CredentialType::Basic,
+        ciphersuite.signature_scheme(),
+        &crypto,
+    )
+    .unwrap();
+    let bob_credential_bundle = CredentialBundle::new(
+        "Bob".into(),
+        CredentialType::Basic,
+        ciphersuite.signature_scheme(),
+        &crypto,
+    )
+    .unwrap();
+    let alice_key_package_bundle = KeyPackageBundle::new(
+        &[ciphersuite.name()],
+        &alice_credential_bundle,
+        &crypto,
+        Vec::new(),
+    )
+    .unwrap();
+    let bob_key_package_bundle = KeyPackageBundle::new(
+        &[ciphersuite.name()],
+        &bob_credential_bundle,
+        &crypto,
+        Vec::new(),
+    )
+    .unwrap();
+
+    // === Alice creates a group and adds Bob ===
+    let mut alice_group = MlsGroup::builder(GroupId::random(&crypto), alice_key_package_bundle)
+        .build(&crypto)
+        .expect("Error creating group.");
+    let bob_add_proposal = alice_group
+        .create_add_proposal(
+            framing_parameters,
+            &alice_credential_bundle,
+            bob_key_package_bundle.key_package().clone(),
+            &crypto,
+        )
+        .expect("Could not create proposal.");
+    let proposal_store = ProposalStore::from_staged_proposal(
+        StagedProposal::from_mls_plaintext(ciphersuite, &crypto, bob_add_proposal)
+            .expect("Could not create StagedProposal."),
+    );
+    let params = CreateCommitParams::builder()
+        .framing_parameters(framing_parameters)
+        .credential_bundle(&alice_credential_bundle)
+        .proposal_store(&proposal_store)
+        .force_self_update(false)
+        .build();
+    let (commit, welcome_option, _kpb_option) = alice_group
+        .create_commit(params, &crypto)
+        .expect("Error creating commit");
+    let staged_commit = alice_group
+        .stage_commit(&commit, &proposal_store, &[], None, &crypto)
+        .expect("Error staging commit");
+    alice_group.merge_commit(staged_commit);
+
+    let bob_group = MlsGroup::new_from_welcome(
+        welcome_option.expect("Expected a Welcome message."),
+        Some(alice_group.tree().public_key_tree_copy()),
+        bob_key_package_bundle,
+        None,
+        &crypto,
+    )
+    .expect("Error joining group.");
+
+    // Synced members compute the same safety number.
+    let alice_safety_number = alice_group
+        .safety_number(&crypto, 12)
+        .expect("Could not compute safety number.");
+    assert_eq!(alice_safety_number.len(), 12);
+    assert!(alice_safety_number.chars().all(|c| c.is_ascii_digit()));
+    assert_eq!(
+        alice_safety_number,
+        bob_group
+            .safety_number(&crypto, 12)
+            .expect("Could not compute safety number.")
+    );
+
+    // === Alice updates without Bob processing the commit ===
+    let proposal_store = ProposalStore::new();
+    let params = CreateCommitParams::builder()
+        .framing_parameters(framing_parameters)
+        .credential_bundle(&alice_credential_bundle)
+        .proposal_store(&proposal_store)
+        .build();
+    let (commit, _welcome_option, kpb_option) = alice_group
+        .create_commit(params, &crypto)
+        .expect("Error creating commit");
+    let staged_commit = alice_group
+        .stage_commit(
+            &commit,
+            &proposal_store,
+            &[kpb_option.expect("Expected a KeyPackageBundle.")],
+            None,
+            &crypto,
+        )
+        .expect("Error staging commit");
+    alice_group.merge_commit(staged_commit);
+
+    // The safety numbers of the diverged members differ.
+    assert_ne!(
+        alice_group
+            .safety_number(&crypto, 12)
+            .expect("Could not compute safety number."),
+        bob_group
+            .safety_number(&crypto, 12)
+            .expect("Could not compute safety number.")
+    );
+}
+
+#[test]
+fn test_past_epoch_decryption() {
+    let crypto = OpenMlsRustCrypto::default();
+    let ciphersuite = &Config::supported_ciphersuites()[0];
+    let framing_parameters = FramingParameters::new(&[], WireFormat::MlsPlaintext);
+
+    let alice_credential_bundle = CredentialBundle::new(
+        "Alice".into(),
+        CredentialType::Basic,
+        ciphersuite.signature_scheme(),
+        &crypto,
+    )
+    .unwrap();
+    let bob_credential_bundle = CredentialBundle::new(
+        "Bob".into(),
+        CredentialType::Basic,
+        ciphersuite.signature_scheme(),
+        &crypto,
+    )
+    .unwrap();
+    let alice_key_package_bundle = KeyPackageBundle::new(
+        &[ciphersuite.name()],
+        &alice_credential_bundle,
+        &crypto,
+        Vec::new(),
+    )
+    .unwrap();
+    let bob_key_package_bundle = KeyPackageBundle::new(
+        &[ciphersuite.name()],
+        &bob_credential_bundle,
+        &crypto,
+        Vec::new(),
+    )
+    .unwrap();
+
+    // === Alice creates a group and adds Bob ===
+    let mut alice_group = MlsGroup::builder(GroupId::random(&crypto), alice_key_package_bundle)
+        .build(&crypto)
+        .expect("Error creating group.");
+    let bob_add_proposal = alice_group
+        .create_add_proposal(
+            framing_parameters,
+            &alice_credential_bundle,
+            bob_key_package_bundle.key_package().clone(),
+            &crypto,
+        )
+        .expect("Could not create proposal.");
+    let proposal_store = ProposalStore::from_staged_proposal(
+        StagedProposal::from_mls_plaintext(ciphersuite, &crypto, bob_add_proposal)
+            .expect("Could not create StagedProposal."),
+    );
+    let params = CreateCommitParams::builder()
+        .framing_parameters(framing_parameters)
+        .credential_bundle(&alice_credential_bundle)
+        .proposal_store(&proposal_store)
+        .force_self_update(false)
+        .build();
+    let (commit, welcome_option, _kpb_option) = alice_group
+        .create_commit(params, &crypto)
+        .expect("Error creating commit");
+    let staged_commit = alice_group
+        .stage_commit(&commit, &proposal_store, &[], None, &crypto)
+        .expect("Error staging commit");
+    alice_group.merge_commit(staged_commit);
+
+    let mut bob_group = MlsGroup::new_from_welcome(
+        welcome_option.expect("Expected a Welcome message."),
+        Some(alice_group.tree().public_key_tree_copy()),
+        bob_key_package_bundle,
+        None,
+        &crypto,
+    )
+    .expect("Error joining group.");
+    // Bob retains the secrets of one past epoch.
+    bob_group.set_max_past_epochs(1);
+
+    // === Alice sends two messages in epoch 1 ===
+    let first_message = alice_group
+        .create_application_message(&[], b"first", &alice_credential_bundle, 0, &crypto)
+        .expect("Error creating application message.");
+    let second_message = alice_group
+        .create_application_message(&[], b"second", &alice_credential_bundle, 0, &crypto)
+        .expect("Error creating application message.");
+
+    // Alice updates and both members move on to the next epoch.
+    let self_update = |alice_group: &mut MlsGroup, bob_group: &mut MlsGroup| {
+        let proposal_store = ProposalStore::new();
+        let params = CreateCommitParams::builder()
+            .framing_parameters(framing_parameters)
+            .credential_bundle(&alice_credential_bundle)
+            .proposal_store(&proposal_store)
+            .build();
+        let (commit, _welcome_option, kpb_option) = alice_group
+            .create_commit(params, &crypto)
+            .expect("Error creating commit");
+        let staged_commit = alice_group
+            .stage_commit(
+                &commit,
+                &proposal_store,
+                &[kpb_option.expect("Expected a KeyPackageBundle.")],
+                None,
+                &crypto,
+            )
+            .expect("Error staging commit");
+        alice_group.merge_commit(staged_commit);
+        let staged_commit = bob_group
+            .stage_commit(&commit, &proposal_store, &[], None, &crypto)
+            .expect("Error staging commit");
+        bob_group.merge_commit(staged_commit);
+    };
+
+    // === Epoch 2: epoch 1 is still within the window ===
+    self_update(&mut alice_group, &mut bob_group);
+    let plaintext = bob_group
+        .decrypt(&first_message, &crypto)
+        .expect("Error decrypting message from the previous epoch.");
+    assert_eq!(plaintext.epoch(), GroupEpoch(1));
+    assert_eq!(plaintext.content_type(), ContentType::Application);
+
+    // === Epoch 3: epoch 1 is outside of the window ===
+    self_update(&mut alice_group, &mut bob_group);
+    assert_eq!(
+        bob_group
+            .decrypt(&second_message, &crypto)
+            .expect_err("Decrypting a message outside of the window must fail."),
+        MlsGroupError::EpochNotRetained
+    );
+}
+
+#[test]
+fn test_setup_group_with_members() {
+    let crypto = OpenMlsRustCrypto::default();
+    let ciphersuite = &Config::supported_ciphersuites()[0];
+
+    let (creator_group, members) =
+        setup_group_with_members(ciphersuite, &["Alice", "Bob", "Charlie", "Dave"], &crypto);
+    assert_eq!(members.len(), 3);
+    assert_eq!(creator_group.tree().leaf_count().as_usize(), 4);
+
+    let tree_hash = creator_group.tree().tree_hash(&crypto);
+    for (credential_bundle, group) in members.iter() {
+        assert_eq!(group.context().epoch(), creator_group.context().epoch());
+        assert_eq!(group.tree().tree_hash(&crypto), tree_hash);
+        assert!(group
+            .tree()
+            .key_packages()
+            .any(|key_package| key_package.credential() == credential_bundle.credential()));
+    }
+}
+
+#[test]
+fn test_confirmation_tag() {
+    let crypto = OpenMlsRustCrypto::default();
+    let ciphersuite = &Config::supported_ciphersuites()[0];
+    let framing_parameters = FramingParameters::new(&[], WireFormat::MlsPlaintext);
+
+    let (alice_group, mut members) =
+        setup_group_with_members(ciphersuite, &["Alice", "Bob"], &crypto);
+    let (bob_credential_bundle, mut bob_group) = members.remove(0);
+
+    // Synced members have the same confirmation tag.
+    assert_eq!(alice_group.confirmation_tag(), bob_group.confirmation_tag());
+
+    // === Bob updates without Alice processing the commit ===
+    let proposal_store = ProposalStore::new();
+    let params = CreateCommitParams::builder()
+        .framing_parameters(framing_parameters)
+        .credential_bundle(&bob_credential_bundle)
+        .proposal_store(&proposal_store)
+        .build();
+    let (commit, _welcome_option, kpb_option) = bob_group
+        .create_commit(params, &crypto)
+        .expect("Error creating commit");
+    let staged_commit = bob_group
+        .stage_commit(
+            &commit,
+            &proposal_store,
+            &[kpb_option.expect("Expected a KeyPackageBundle.")],
+            None,
+            &crypto,
+        )
+        .expect("Error staging commit");
+    bob_group.merge_commit(staged_commit);
+
+    // The tag is the one Bob sent along with the commit, but Alice's tag
+    // is still the one of the previous epoch.
+    assert_eq!(
+        Some(bob_group.confirmation_tag()),
+        commit.confirmation_tag()
+    );
+    assert_ne!(alice_group.confirmation_tag(), bob_group.confirmation_tag());
+}
+
+#[test]
+fn test_estimate_commit_size() {
+    let crypto = OpenMlsRustCrypto::default();
+    let framing_parameters = FramingParameters::new(&[], WireFormat::MlsPlaintext);
+
+    // The estimates must be within 20% of the actual sizes.
+    let assert_close = |estimate: usize, actual: usize| {
+        assert!(
+            estimate * 5 >= actual * 4 && estimate * 5 <= actual * 6,
+            "Estimate {} is too far off the actual size {}",
+            estimate,
+            actual
+        );
+    };
+
+    for ciphersuite in Config::supported_ciphersuites() {
+        let (_alice_group, mut members) =
+            setup_group_with_members(ciphersuite, &["Alice", "Bob", "Charlie"], &crypto);
+        let (bob_credential_bundle, mut bob_group) = members.remove(0);
+
+        // === Bob adds Dave and updates his own leaf ===
+        let dave_credential_bundle = CredentialBundle::new(
+            "Dave".into(),
+            CredentialType::Basic,
+            ciphersuite.signature_scheme(),
+            &crypto,
+        )
+        .expect("Could not create credential bundle.");
+        let dave_key_package_bundle = KeyPackageBundle::new(
+            &[ciphersuite.name()],
+            &dave_credential_bundle,
+            &crypto,
+            Vec::new(),
+        )
+        .expect("Could not create key package bundle.");
+        let dave_add_proposal = bob_group
+            .create_add_proposal(
+                framing_parameters,
+                &bob_credential_bundle,
+                dave_key_package_bundle.key_package().clone(),
+                &crypto,
+            )
+            .expect("Could not create proposal.");
+        let proposal_store = ProposalStore::from_staged_proposal(
+            StagedProposal::from_mls_plaintext(ciphersuite, &crypto, dave_add_proposal)
+                .expect("Could not create StagedProposal."),
+        );
+
+        let commit_estimate = bob_group.estimate_commit_size(&proposal_store, true);
+        let welcome_estimate = bob_group.estimate_welcome_size(&proposal_store);
+
+        let params = CreateCommitParams::builder()
+            .framing_parameters(framing_parameters)
+            .credential_bundle(&bob_credential_bundle)
+            .proposal_store(&proposal_store)
+            .force_self_update(true)
+            .build();
+        let (commit, welcome_option, _kpb_option) = bob_group
+            .create_commit(params, &crypto)
+            .expect("Error creating commit");
+        let welcome = welcome_option.expect("Expected a Welcome message.");
+
+        assert_close(commit_estimate, commit.tls_serialized_len());
+        assert_close(welcome_estimate, welcome.tls_serialized_len());
+
+        // Without Add proposals there is no Welcome message.
+        assert_eq!(bob_group.estimate_welcome_size(&ProposalStore::new()), 0);
+    }
+}
+
+#[test]
+fn test_external_commit() {
+    let crypto = OpenMlsRustCrypto::default();
+    let ciphersuite = &Config::supported_ciphersuites()[0];
+    let framing_parameters = FramingParameters::new(&[], WireFormat::MlsPlaintext);
+
+    let (mut alice_group, mut members) =
+        setup_group_with_members(ciphersuite, &["Alice", "Bob"], &crypto);
+    let (bob_credential_bundle, bob_group) = members.remove(0);
+
+    // === Bob creates a regular commit ===
+    let proposal_store = ProposalStore::new();
+    let params = CreateCommitParams::builder()
+        .framing_parameters(framing_parameters)
+        .credential_bundle(&bob_credential_bundle)
+        .proposal_store(&proposal_store)
+        .build();
+    let (mut mls_plaintext, _welcome_option, _kpb_option) = bob_group
+        .create_commit(params, &crypto)
+        .expect("Error creating commit");
+    let commit = match mls_plaintext.content() {
+        MlsPlaintextContentType::Commit(commit) => commit.clone(),
+        _ => panic!("Wrong content type"),
+    };
+    assert!(!commit.is_external());
+
+    // === Turn it into an external commit ===
+    let external_commit = Commit {
+        proposals: vec![ProposalOrRef::Proposal(Proposal::ExternalInit(
+            ExternalInitProposal::from(vec![1, 2, 3]),
+        ))]
+        .into(),
+        path: commit.path,
+    };
+    assert!(external_commit.is_external());
+
+    // External commits are not staged as member commits.
+    mls_plaintext.set_content(MlsPlaintextContentType::Commit(external_commit));
+    let error = alice_group
+        .stage_commit(&mls_plaintext, &proposal_store, &[], None, &crypto)
+        .expect_err("Staging an external commit must fail.");
+    assert_eq!(
+        error,
+        MlsGroupError::StageCommitError(StageCommitError::ExternalCommitNotSupported)
+    );
+}
+
+#[test]
+fn test_leaf_parent_hash() {
+    let crypto = OpenMlsRustCrypto::default();
+    let ciphersuite = &Config::supported_ciphersuites()[0];
+    let framing_parameters = FramingParameters::new(&[], WireFormat::MlsPlaintext);
+
+    let (mut alice_group, mut members) =
+        setup_group_with_members(ciphersuite, &["Alice", "Bob", "Charlie"], &crypto);
+    let (bob_credential_bundle, mut bob_group) = members.remove(0);
+    let bob_index = LeafIndex::from(1u32);
+
+    // === Bob updates and Alice processes the commit ===
+    let proposal_store = ProposalStore::new();
+    let params = CreateCommitParams::builder()
+        .framing_parameters(framing_parameters)
+        .credential_bundle(&bob_credential_bundle)
+        .proposal_store(&proposal_store)
+        .build();
+    let (commit, _welcome_option, kpb_option) = bob_group
+        .create_commit(params, &crypto)
+        .expect("Error creating commit");
+    let staged_commit = bob_group
+        .stage_commit(
+            &commit,
+            &proposal_store,
+            &[kpb_option.expect("Expected a KeyPackageBundle.")],
+            None,
+            &crypto,
+        )
+        .expect("Error staging commit");
+    bob_group.merge_commit(staged_commit);
+    let staged_commit = alice_group
+        .stage_commit(&commit, &proposal_store, &[], None, &crypto)
+        .expect("Error staging commit");
+    alice_group.merge_commit(staged_commit);
+
+    // Bob's new leaf carries the parent hash of his direct path.
+    {
+        let tree = alice_group.tree();
+        tree.verify_leaf_parent_hash(&crypto, bob_index)
+            .expect("Bob's leaf parent hash is invalid.");
+        tree.verify_parent_hashes(&crypto)
+            .expect("Parent hashes are invalid.");
+    }
+
+    // === Tamper with the parent hash in Bob's leaf ===
+    let bob_key_package = bob_group.tree().own_key_package().clone();
+    let mut payload = KeyPackagePayload::from(bob_key_package);
+    payload.add_extension(Extension::ParentHash(ParentHashExtension::new(
+        &vec![0u8; ciphersuite.hash_length()],
+    )));
+    let tampered_key_package = payload
+        .sign(&crypto, &bob_credential_bundle)
+        .expect("Error signing key package");
+    alice_group.tree.borrow_mut().nodes[bob_index] = Node::new_leaf(Some(tampered_key_package));
+
+    let tree = alice_group.tree();
+    assert_eq!(
+        tree.verify_leaf_parent_hash(&crypto, bob_index),
+        Err(ParentHashError::LeafParentHashMismatch)
+    );
+    assert!(tree.verify_parent_hashes(&crypto).is_err());
+}
+
+#[test]
+fn test_sender_data() {
+    let crypto = OpenMlsRustCrypto::default();
+    let ciphersuite = &Config::supported_ciphersuites()[0];
+
+    let (mut alice_group, mut members) =
+        setup_group_with_members(ciphersuite, &["Alice", "Bob"], &crypto);
+    let (bob_credential_bundle, mut bob_group) = members.remove(0);
+
+    // === Bob (leaf 1) sends two application messages ===
+    for expected_generation in 0..2 {
+        let mls_ciphertext = bob_group
+            .create_application_message(&[], b"Hello", &bob_credential_bundle, 0, &crypto)
+            .expect("Error creating application message.");
+
+        let unverified_message = alice_group
+            .parse_message(MlsMessageIn::Ciphertext(mls_ciphertext), &crypto)
+            .expect("Error parsing message.");
+        let sender_data = unverified_message
+            .sender_data()
+            .expect("Expected sender data for an MlsCiphertext.");
+        assert_eq!(sender_data.sender(), LeafIndex::from(1u32));
+        assert_eq!(
+            sender_data.sender(),
+            unverified_message.sender().to_leaf_index()
+        );
+        assert_eq!(sender_data.generation(), expected_generation);
+    }
+}
+
+#[test]
+fn test_concurrent_proposals() {
+    let crypto = OpenMlsRustCrypto::default();
+    for ciphersuite in Config::supported_ciphersuites() {
+        let framing_parameters = FramingParameters::new(&[], WireFormat::MlsPlaintext);
+
+        // Dave creates the group at leaf 0.
+        let (_dave_group, mut members) =
+            setup_group_with_members(ciphersuite, &["Dave", "Alice", "Bob", "Carol"], &crypto);
+        let (alice_credential_bundle, mut alice_group) = members.remove(0);
+        let (bob_credential_bundle, mut bob_group) = members.remove(0);
+        let (carol_credential_bundle, mut carol_group) = members.remove(0);
+
+        // === Alice adds Eve, Bob removes Dave and Carol updates ===
+        let eve_credential_bundle = CredentialBundle::new(
+            "Eve".into(),
+            CredentialType::Basic,
+            ciphersuite.signature_scheme(),
+            &crypto,
+        )
+        .expect("Could not create credential bundle.");
+        let eve_key_package_bundle = KeyPackageBundle::new(
+            &[ciphersuite.name()],
+            &eve_credential_bundle,
+            &crypto,
+            Vec::new(),
+        )
+        .expect("Could not create key package bundle.");
+        let add_proposal = alice_group
+            .create_add_proposal(
+                framing_parameters,
+                &alice_credential_bundle,
+                eve_key_package_bundle.key_package().clone(),
+                &crypto,
+            )
+            .expect("Could not create proposal.");
+
+        let remove_proposal = bob_group
+            .create_remove_proposal(
+                framing_parameters,
+                &bob_credential_bundle,
+                LeafIndex::from(0u32),
+                &crypto,
+            )
+            .expect("Could not create proposal.");
+
+        let carol_update_key_package_bundle = KeyPackageBundle::new(
+            &[ciphersuite.name()],
+            &carol_credential_bundle,
+            &crypto,
+            Vec::new(),
+        )
+        .expect("Could not create key package bundle.");
+        let update_proposal = carol_group
+            .create_update_proposal(
+                framing_parameters,
+                &carol_credential_bundle,
+                carol_update_key_package_bundle.key_package().clone(),
+                &crypto,
+            )
+            .expect("Could not create proposal.");
+
+        // All members receive the proposals in the same order.
+        let mut proposal_store = ProposalStore::new();
+        for proposal in [add_proposal, remove_proposal, update_proposal] {
+            proposal_store.add(
+                StagedProposal::from_mls_plaintext(ciphersuite, &crypto, proposal)
+                    .expect("Could not create StagedProposal."),
+            );
+        }
+
+        // === Alice commits all three proposals ===
+        let params = CreateCommitParams::builder()
+            .framing_parameters(framing_parameters)
+            .credential_bundle(&alice_credential_bundle)
+            .proposal_store(&proposal_store)
+            .force_self_update(false)
+            .build();
+        let (commit, welcome_option, kpb_option) = alice_group
+            .create_commit(params, &crypto)
+            .expect("Error creating commit");
+
+        let staged_commit = alice_group
+            .stage_commit(
+                &commit,
+                &proposal_store,
+                &[kpb_option.expect("Expected a KeyPackageBundle.")],
+                None,
+                &crypto,
+            )
+            .expect("Error staging commit");
+        assert_eq!(staged_commit.add_proposals().count(), 1);
+        assert_eq!(staged_commit.remove_proposals().count(), 1);
+        assert_eq!(staged_commit.update_proposals().count(), 1);
+        alice_group.merge_commit(staged_commit);
+
+        let staged_commit = bob_group
+            .stage_commit(&commit, &proposal_store, &[], None, &crypto)
+            .expect("Error staging commit");
+        bob_group.merge_commit(staged_commit);
+
+        let staged_commit = carol_group
+            .stage_commit(
+                &commit,
+                &proposal_store,
+                &[carol_update_key_package_bundle],
+                None,
+                &crypto,
+            )
+            .expect("Error staging commit");
+        carol_group.merge_commit(staged_commit);
+
+        let eve_group = MlsGroup::new_from_welcome(
+            welcome_option.expect("Expected a Welcome message."),
+            Some(alice_group.tree().public_key_tree_copy()),
+            eve_key_package_bundle,
+            None,
+            &crypto,
+        )
+        .expect("Error joining group.");
+
+        // === All remaining members converge on the same state ===
+        let alice_tree_hash = alice_group.tree().tree_hash(&crypto);
+        let alice_exported_secret = alice_group
+            .export_secret(&crypto, "test", &[], 32)
+            .expect("Error exporting secret.");
+        for group in [&bob_group, &carol_group, &eve_group] {
+            assert_eq!(group.context().epoch(), alice_group.context().epoch());
+            assert_eq!(group.confirmation_tag(), alice_group.confirmation_tag());
+            assert_eq!(group.tree().tree_hash(&crypto), alice_tree_hash);
+            assert_eq!(
+                group
+                    .export_secret(&crypto, "test", &[], 32)
+                    .expect("Error exporting secret."),
+                alice_exported_secret
+            );
+        }
+    }
+}
+
+#[test]
+fn test_remove_and_add_fills_freed_leaf() {
+    let crypto = OpenMlsRustCrypto::default();
+    for ciphersuite in Config::supported_ciphersuites() {
+        let framing_parameters = FramingParameters::new(&[], WireFormat::MlsPlaintext);
+
+        let (mut alice_group, mut members) =
+            setup_group_with_members(ciphersuite, &["Alice", "Bob", "Charlie"], &crypto);
+        let (_bob_credential_bundle, _bob_group) = members.remove(0);
+        let (charlie_credential_bundle, mut charlie_group) = members.remove(0);
+        let leaf_count = charlie_group.tree().leaf_count();
+
+        // === Charlie removes Bob (leaf 1) and adds Dave in one commit ===
+        let dave_credential_bundle = CredentialBundle::new(
+            "Dave".into(),
+            CredentialType::Basic,
+            ciphersuite.signature_scheme(),
+            &crypto,
+        )
+        .expect("Could not create credential bundle.");
+        let dave_key_package_bundle = KeyPackageBundle::new(
+            &[ciphersuite.name()],
+            &dave_credential_bundle,
+            &crypto,
+            Vec::new(),
+        )
+        .expect("Could not create key package bundle.");
+        let remove_proposal = charlie_group
+            .create_remove_proposal(
+                framing_parameters,
+                &charlie_credential_bundle,
+                LeafIndex::from(1u32),
+                &crypto,
+            )
+            .expect("Could not create proposal.");
+        let add_proposal = charlie_group
+            .create_add_proposal(
+                framing_parameters,
+                &charlie_credential_bundle,
+                dave_key_package_bundle.key_package().clone(),
+                &crypto,
+            )
+            .expect("Could not create proposal.");
+        let mut proposal_store = ProposalStore::new();
+        for proposal in [remove_proposal, add_proposal] {
+            proposal_store.add(
+                StagedProposal::from_mls_plaintext(ciphersuite, &crypto, proposal)
+                    .expect("Could not create StagedProposal."),
+            );
+        }
+
+        let params = CreateCommitParams::builder()
+            .framing_parameters(framing_parameters)
+            .credential_bundle(&charlie_credential_bundle)
+            .proposal_store(&proposal_store)
+            .force_self_update(false)
+            .build();
+        let (commit, welcome_option, kpb_option) = charlie_group
+            .create_commit(params, &crypto)
+            .expect("Error creating commit");
+        let staged_commit = charlie_group
+            .stage_commit(
+                &commit,
+                &proposal_store,
+                &[kpb_option.expect("Expected a KeyPackageBundle.")],
+                None,
+                &crypto,
+            )
+            .expect("Error staging commit");
+        charlie_group.merge_commit(staged_commit);
+        let staged_commit = alice_group
+            .stage_commit(&commit, &proposal_store, &[], None, &crypto)
+            .expect("Error staging commit");
+        alice_group.merge_commit(staged_commit);
+
+        let dave_group = MlsGroup::new_from_welcome(
+            welcome_option.expect("Expected a Welcome message."),
+            Some(charlie_group.tree().public_key_tree_copy()),
+            dave_key_package_bundle,
+            None,
+            &crypto,
+        )
+        .expect("Error joining group.");
+
+        // Dave took Bob's leaf and the tree didn't grow.
+        for group in [&alice_group, &charlie_group, &dave_group] {
+            let tree = group.tree();
+            assert_eq!(tree.leaf_count(), leaf_count);
+            let dave_key_package = tree.nodes[LeafIndex::from(1u32)]
+                .key_package()
+                .expect("Leaf 1 is blank.");
+            assert_eq!(dave_key_package.credential().identity(), b"Dave");
+        }
+        assert_eq!(dave_group.tree().own_node_index(), LeafIndex::from(1u32));
+    }
+}
+
+#[test]
+fn test_key_package_bundle_consumed() {
+    let crypto = OpenMlsRustCrypto::default();
+    let ciphersuite = &Config::supported_ciphersuites()[0];
+    let framing_parameters = FramingParameters::new(&[], WireFormat::MlsPlaintext);
+
+    let setup_client = |id: &str| {
+        let credential_bundle = CredentialBundle::new(
+            id.into(),
+            CredentialType::Basic,
+            ciphersuite.signature_scheme(),
+            &crypto,
+        )
+        .unwrap();
+        let key_package_bundle =
+            KeyPackageBundle::new(&[ciphersuite.name()], &credential_bundle, &crypto, vec![])
+                .unwrap();
+        (credential_bundle, key_package_bundle)
+    };
+    let (alice_credential_bundle, alice_key_package_bundle) = setup_client("Alice");
+    let (_bob_credential_bundle, bob_key_package_bundle) = setup_client("Bob");
+
+    let mut alice_group = MlsGroup::builder(GroupId::random(&crypto), alice_key_package_bundle)
+        .build(&crypto)
+        .expect("Error creating group.");
+
+    // Alice adds Bob
+    let bob_add_proposal = alice_group
+        .create_add_proposal(
+            framing_parameters,
+            &alice_credential_bundle,
+            bob_key_package_bundle.key_package().clone(),
+            &crypto,
+        )
+        .expect("Could not create proposal.");
+    let proposal_store = ProposalStore::from_staged_proposal(
+        StagedProposal::from_mls_plaintext(ciphersuite, &crypto, bob_add_proposal)
+            .expect("Could not create StagedProposal."),
+    );
+    let params = CreateCommitParams::builder()
+        .framing_parameters(framing_parameters)
+        .credential_bundle(&alice_credential_bundle)
+        .proposal_store(&proposal_store)
+        .force_self_update(false)
+        .build();
+    let (commit, welcome_option, _kpb_option) = alice_group
+        .create_commit(params, &crypto)
+        .expect("Error creating commit");
+    let staged_commit = alice_group
+        .stage_commit(&commit, &proposal_store, &[], None, &crypto)
+        .expect("Error staging commit");
+    alice_group.merge_commit(staged_commit);
+
+    // Bob's bundle is only consumed once he uses it to join.
+    assert!(!bob_key_package_bundle.is_consumed(&crypto));
+    let _bob_group = MlsGroup::new_from_welcome(
+        welcome_option.expect("Expected a Welcome message."),
+        Some(alice_group.tree().public_key_tree_copy()),
+        bob_key_package_bundle.clone(),
+        None,
+        &crypto,
+    )
+    .expect("Error joining group from Welcome");
+    assert!(bob_key_package_bundle.is_consumed(&crypto));
+}
+
+#[test]
+fn test_welcome_only_for_added_members() {
+    let crypto = OpenMlsRustCrypto::default();
+    for ciphersuite in Config::supported_ciphersuites() {
+        let framing_parameters = FramingParameters::new(&[], WireFormat::MlsPlaintext);
+
+        let (_alice_group, mut members) =
+            setup_group_with_members(ciphersuite, &["Alice", "Bob"], &crypto);
+        let (bob_credential_bundle, bob_group) = members.remove(0);
+
+        // === Bob adds Charlie and updates his own path ===
+        let charlie_credential_bundle = CredentialBundle::new(
+            "Charlie".into(),
+            CredentialType::Basic,
+            ciphersuite.signature_scheme(),
+            &crypto,
+        )
+        .expect("Could not create credential bundle.");
+        let charlie_key_package_bundle = KeyPackageBundle::new(
+            &[ciphersuite.name()],
+            &charlie_credential_bundle,
+            &crypto,
+            Vec::new(),
+        )
+        .expect("Could not create key package bundle.");
+        let add_proposal = bob_group
+            .create_add_proposal(
+                framing_parameters,
+                &bob_credential_bundle,
+                charlie_key_package_bundle.key_package().clone(),
+                &crypto,
+            )
+            .expect("Could not create proposal.");
+        let proposal_store = ProposalStore::from_staged_proposal(
+            StagedProposal::from_mls_plaintext(ciphersuite, &crypto, add_proposal)
+                .expect("Could not create StagedProposal."),
+        );
+        let params = CreateCommitParams::builder()
+            .framing_parameters(framing_parameters)
+            .credential_bundle(&bob_credential_bundle)
+            .proposal_store(&proposal_store)
+            .force_self_update(true)
+            .build();
+        let (_commit, welcome_option, _kpb_option) = bob_group
+            .create_commit(params, &crypto)
+            .expect("Error creating commit");
+
+        // Only Charlie gets Welcome secrets. Alice learns the new path from
+        // the commit itself.
+        let welcome = welcome_option.expect("Expected a Welcome message.");
+        assert_eq!(welcome.secrets().len(), 1);
+        assert_eq!(
+            welcome.secrets()[0].key_package_hash.as_slice(),
+            charlie_key_package_bundle.key_package().hash(&crypto)
+        );
+    }
+}
+
+/// Send an application message from `sender_group` to `receiver_group` and
+/// assert that the receiver decrypts it to the original message.
+fn assert_application_message_round_trip(
+    sender_group: &mut MlsGroup,
+    sender_credential_bundle: &CredentialBundle,
+    receiver_group: &mut MlsGroup,
+    message: &[u8],
+    backend: &impl OpenMlsCryptoProvider,
+) {
+    let ciphertext = sender_group
+        .create_application_message(&[], message, sender_credential_bundle, 0, backend)
+        .expect("Error creating application message.");
+    let verifiable_plaintext = receiver_group
+        .decrypt(&ciphertext, backend)
+        .expect("Error decrypting application message.");
+    let plaintext = receiver_group
+        .verify(verifiable_plaintext, backend)
+        .expect("Error verifying application message.");
+    assert_eq!(
+        plaintext
+            .as_application_message()
+            .expect("Expected an application message."),
+        message
+    );
+}
+
+#[test]
+fn test_welcome_secret_tree_round_trip() {
+    let crypto = OpenMlsRustCrypto::default();
+    for ciphersuite in Config::supported_ciphersuites() {
+        let framing_parameters = FramingParameters::new(&[], WireFormat::MlsPlaintext);
+
+        let alice_credential_bundle = CredentialBundle::new(
+            "Alice".into(),
+            CredentialType::Basic,
+            ciphersuite.signature_scheme(),
+            &crypto,
+        )
+        .unwrap();
+        let bob_credential_bundle = CredentialBundle::new(
+            "Bob".into(),
+            CredentialType::Basic,
+            ciphersuite.signature_scheme(),
+            &crypto,
+        )
+        .unwrap();
+        let alice_key_package_bundle = KeyPackageBundle::new(
+            &[ciphersuite.name()],
+            &alice_credential_bundle,
+            &crypto,
+            Vec::new(),
+        )
+        .unwrap();
+        let bob_key_package_bundle = KeyPackageBundle::new(
+            &[ciphersuite.name()],
+            &bob_credential_bundle,
+            &crypto,
+            Vec::new(),
+        )
+        .unwrap();
+
+        // === Alice creates a group and adds Bob ===
+        let mut alice_group = MlsGroup::builder(GroupId::random(&crypto), alice_key_package_bundle)
+            .build(&crypto)
+            .expect("Error creating group.");
+        let bob_add_proposal = alice_group
+            .create_add_proposal(
+                framing_parameters,
+                &alice_credential_bundle,
+                bob_key_package_bundle.key_package().clone(),
+                &crypto,
+            )
+            .expect("Could not create proposal.");
+        let proposal_store = ProposalStore::from_staged_proposal(
+            StagedProposal::from_mls_plaintext(ciphersuite, &crypto, bob_add_proposal)
+                .expect("Could not create StagedProposal."),
+        );
+        let params = CreateCommitParams::builder()
+            .framing_parameters(framing_parameters)
+            .credential_bundle(&alice_credential_bundle)
+            .proposal_store(&proposal_store)
+            .force_self_update(false)
+            .build();
+        let (commit, welcome_option, _kpb_option) = alice_group
+            .create_commit(params, &crypto)
+            .expect("Error creating commit");
+        let staged_commit = alice_group
+            .stage_commit(&commit, &proposal_store, &[], None, &crypto)
+            .expect("Error staging commit");
+        alice_group.merge_commit(staged_commit);
+
+        // === Bob joins from the Welcome ===
+        let mut bob_group = MlsGroup::new_from_welcome(
+            welcome_option.expect("Expected a Welcome message."),
+            Some(alice_group.tree().public_key_tree_copy()),
+            bob_key_package_bundle,
+            None,
+            &crypto,
+        )
+        .expect("Error joining group.");
+
+        // Both derived the same secret tree from the same encryption secret.
+        assert_eq!(
+            *alice_group.secret_tree.borrow(),
+            *bob_group.secret_tree.borrow()
+        );
+
+        // === Alice and Bob exchange application messages ===
+        assert_application_message_round_trip(
+            &mut alice_group,
+            &alice_credential_bundle,
+            &mut bob_group,
+            b"Hello Bob",
+            &crypto,
+        );
+        assert_application_message_round_trip(
+            &mut bob_group,
+            &bob_credential_bundle,
+            &mut alice_group,
+            b"Hello Alice",
+            &crypto,
+        );
+    }
+}
+
+/// A crypto provider whose randomness always returns the same bytes, so that
+/// every fresh leaf secret, and thus every new leaf key, is the same.
+#[derive(Default)]
+struct FixedRandomnessProvider {
+    backend: OpenMlsRustCrypto,
+    rand: FixedRand,
+}
+
+#[derive(Default)]
+struct FixedRand;
+
+impl openmls_traits::random::OpenMlsRand for FixedRand {
+    type Error = &'static str;
+
+    fn random_array<const N: usize>(&self) -> Result<[u8; N], Self::Error> {
+        Ok([0x42; N])
+    }
+
+    fn random_vec(&self, len: usize) -> Result<Vec<u8>, Self::Error> {
+        Ok(vec![0x42; len])
+    }
+}
+
+impl OpenMlsCryptoProvider for FixedRandomnessProvider {
+    type CryptoProvider = <OpenMlsRustCrypto as OpenMlsCryptoProvider>::CryptoProvider;
+    type RandProvider = FixedRand;
+    type KeyStoreProvider = <OpenMlsRustCrypto as OpenMlsCryptoProvider>::KeyStoreProvider;
+
+    fn crypto(&self) -> &Self::CryptoProvider {
+        self.backend.crypto()
+    }
+
+    fn rand(&self) -> &Self::RandProvider {
+        &self.rand
+    }
+
+    fn key_store(&self) -> &Self::KeyStoreProvider {
+        self.backend.key_store()
+    }
+}
+
+#[test]
+fn test_stale_update_path() {
+    let crypto = FixedRandomnessProvider::default();
+    for ciphersuite in Config::supported_ciphersuites() {
+        let framing_parameters = FramingParameters::new(&[], WireFormat::MlsPlaintext);
+        let alice_credential_bundle = CredentialBundle::new(
+            "Alice".into(),
+            CredentialType::Basic,
+            ciphersuite.signature_scheme(),
+            &crypto,
+        )
+        .unwrap();
+        let alice_key_package_bundle = KeyPackageBundle::new(
+            &[ciphersuite.name()],
+            &alice_credential_bundle,
+            &crypto,
+            Vec::new(),
+        )
+        .unwrap();
+        let alice_group = MlsGroup::builder(GroupId::random(&crypto), alice_key_package_bundle)
+            .build(&crypto)
+            .expect("Error creating group.");
+
+        // The forced self-update derives the same leaf key again.
+        let proposal_store = ProposalStore::new();
+        let params = CreateCommitParams::builder()
+            .framing_parameters(framing_parameters)
+            .credential_bundle(&alice_credential_bundle)
+            .proposal_store(&proposal_store)
+            .force_self_update(true)
+            .build();
+        assert_eq!(
+            alice_group
+                .create_commit(params, &crypto)
+                .expect_err("A self-update with the same leaf key must fail."),
+            MlsGroupError::StaleUpdatePath
+        );
+    }
+}
+
+#[test]
+fn test_epoch_change_callback() {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    let crypto = OpenMlsRustCrypto::default();
+    let ciphersuite = &Config::supported_ciphersuites()[0];
+    let framing_parameters = FramingParameters::new(&[], WireFormat::MlsPlaintext);
+
+    let setup_client = |id: &str| {
+        let credential_bundle = CredentialBundle::new(
+            id.into(),
+            CredentialType::Basic,
+            ciphersuite.signature_scheme(),
+            &crypto,
+        )
+        .unwrap();
+        let key_package_bundle =
+            KeyPackageBundle::new(&[ciphersuite.name()], &credential_bundle, &crypto, vec![])
+                .unwrap();
+        (credential_bundle, key_package_bundle)
+    };
+    let (alice_credential_bundle, alice_key_package_bundle) = setup_client("Alice");
+    let (_bob_credential_bundle, bob_key_package_bundle) = setup_client("Bob");
+
+    // Each member counts the epoch changes it observes.
+    let counting_config = |counter: &Arc<AtomicUsize>| {
+        let counter = counter.clone();
+        MlsGroupConfig {
+            on_epoch_change: Some(Arc::new(move |_group: &MlsGroup| {
+                counter.fetch_add(1, Ordering::SeqCst);
+            })),
+            ..MlsGroupConfig::default()
+        }
+    };
+    let alice_counter = Arc::new(AtomicUsize::new(0));
+    let bob_counter = Arc::new(AtomicUsize::new(0));
+
+    let mut alice_group = MlsGroup::builder(GroupId::random(&crypto), alice_key_package_bundle)
+        .with_config(counting_config(&alice_counter))
+        .build(&crypto)
+        .expect("Error creating group.");
+    // Creating the group doesn't change the epoch.
+    assert_eq!(alice_counter.load(Ordering::SeqCst), 0);
+
+    // Alice adds Bob
+    let bob_add_proposal = alice_group
+        .create_add_proposal(
+            framing_parameters,
+            &alice_credential_bundle,
+            bob_key_package_bundle.key_package().clone(),
+            &crypto,
+        )
+        .expect("Could not create proposal.");
+    let proposal_store = ProposalStore::from_staged_proposal(
+        StagedProposal::from_mls_plaintext(ciphersuite, &crypto, bob_add_proposal)
+            .expect("Could not create StagedProposal."),
+    );
+    let params = CreateCommitParams::builder()
+        .framing_parameters(framing_parameters)
+        .credential_bundle(&alice_credential_bundle)
+        .proposal_store(&proposal_store)
+        .force_self_update(false)
+        .build();
+    let (commit, welcome_option, _kpb_option) = alice_group
+        .create_commit(params, &crypto)
+        .expect("Error creating commit");
+    let staged_commit = alice_group
+        .stage_commit(&commit, &proposal_store, &[], None, &crypto)
+        .expect("Error staging commit");
+    alice_group.merge_commit(staged_commit);
+    assert_eq!(alice_counter.load(Ordering::SeqCst), 1);
+
+    let mut bob_group = MlsGroup::new_from_welcome_with_config(
+        welcome_option.expect("Expected a Welcome message."),
+        Some(alice_group.tree().public_key_tree_copy()),
+        bob_key_package_bundle,
+        None,
+        counting_config(&bob_counter),
+        &crypto,
+    )
+    .expect("Error joining group from Welcome");
+    assert_eq!(bob_counter.load(Ordering::SeqCst), 1);
+
+    // Alice updates and both members merge the commit.
+    let proposal_store = ProposalStore::new();
+    let params = CreateCommitParams::builder()
+        .framing_parameters(framing_parameters)
+        .credential_bundle(&alice_credential_bundle)
+        .proposal_store(&proposal_store)
+        .force_self_update(true)
+        .build();
+    let (commit, _welcome_option, kpb_option) = alice_group
+        .create_commit(params, &crypto)
+        .expect("Error creating commit");
+    let staged_commit = alice_group
+        .stage_commit(
+            &commit,
+            &proposal_store,
+            &[kpb_option.expect("Expected a KeyPackageBundle.")],
+            None,
+            &crypto,
+        )
+        .expect("Error staging commit");
+    alice_group.merge_commit(staged_commit);
+    let staged_commit = bob_group
+        .stage_commit(&commit, &proposal_store, &[], None, &crypto)
+        .expect("Error staging commit");
+    bob_group.merge_commit(staged_commit);
+
+    assert_eq!(alice_counter.load(Ordering::SeqCst), 2);
+    assert_eq!(bob_counter.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn test_transcript_continuity() {
+    let crypto = OpenMlsRustCrypto::default();
+    for ciphersuite in Config::supported_ciphersuites() {
+        let framing_parameters = FramingParameters::new(&[], WireFormat::MlsPlaintext);
+
+        let (mut alice_group, mut members) =
+            setup_group_with_members(ciphersuite, &["Alice", "Bob"], &crypto);
+        let (bob_credential_bundle, bob_group) = members.remove(0);
+
+        // Bob updates his path.
+        let proposal_store = ProposalStore::new();
+        let params = CreateCommitParams::builder()
+            .framing_parameters(framing_parameters)
+            .credential_bundle(&bob_credential_bundle)
+            .proposal_store(&proposal_store)
+            .force_self_update(true)
+            .build();
+        let (commit, _welcome_option, _kpb_option) = bob_group
+            .create_commit(params, &crypto)
+            .expect("Error creating commit");
+
+        // Alice's interim transcript hash diverges from her current epoch.
+        let interim_transcript_hash = alice_group.interim_transcript_hash.clone();
+        alice_group.interim_transcript_hash = vec![0u8; ciphersuite.hash_length()];
+        assert_eq!(
+            alice_group
+                .stage_commit(&commit, &proposal_store, &[], None, &crypto)
+                .expect_err("Staging with a wrong interim transcript hash must fail."),
+            MlsGroupError::TranscriptContinuityError
+        );
+
+        // With the correct interim transcript hash the commit can be staged.
+        alice_group.interim_transcript_hash = interim_transcript_hash;
+        alice_group
+            .stage_commit(&commit, &proposal_store, &[], None, &crypto)
+            .expect("Error staging commit");
+    }
+}
+
+#[test]
+fn test_tree_hash() {
+    let crypto = OpenMlsRustCrypto::default();
+    for ciphersuite in Config::supported_ciphersuites() {
+        let framing_parameters = FramingParameters::new(&[], WireFormat::MlsPlaintext);
+
+        let (alice_group, mut members) =
+            setup_group_with_members(ciphersuite, &["Alice", "Bob"], &crypto);
+        let (bob_credential_bundle, mut bob_group) = members.remove(0);
+
+        // Members in sync have the same tree hash, which is the one in the
+        // group context.
+        assert_eq!(alice_group.tree_hash(&crypto), bob_group.tree_hash(&crypto));
+        assert_eq!(
+            alice_group.tree_hash(&crypto),
+            alice_group.context().tree_hash.as_slice()
+        );
+
+        // Bob updates his path and merges the commit, Alice doesn't.
+        let proposal_store = ProposalStore::new();
+        let params = CreateCommitParams::builder()
+            .framing_parameters(framing_parameters)
+            .credential_bundle(&bob_credential_bundle)
+            .proposal_store(&proposal_store)
+            .force_self_update(true)
+            .build();
+        let (commit, _welcome_option, kpb_option) = bob_group
+            .create_commit(params, &crypto)
+            .expect("Error creating commit");
+        let staged_commit = bob_group
+            .stage_commit(
+                &commit,
+                &proposal_store,
+                &[kpb_option.expect("Expected a KeyPackageBundle.")],
+                None,
+                &crypto,
+            )
+            .expect("Error staging commit");
+        bob_group.merge_commit(staged_commit);
+
+        assert_ne!(alice_group.tree_hash(&crypto), bob_group.tree_hash(&crypto));
+        assert_eq!(
+            bob_group.tree_hash(&crypto),
+            bob_group.context().tree_hash.as_slice()
+        );
+    }
+}
+
+#[test]
+fn test_require_tree_in_welcome() {
+    let crypto = OpenMlsRustCrypto::default();
+    let ciphersuite = &Config::supported_ciphersuites()[0];
+    let framing_parameters = FramingParameters::new(&[], WireFormat::MlsPlaintext);
+
+    let setup_client = |id: &str| {
+        let credential_bundle = CredentialBundle::new(
+            id.into(),
+            CredentialType::Basic,
+            ciphersuite.signature_scheme(),
+            &crypto,
+        )
+        .unwrap();
+        let key_package_bundle =
+            KeyPackageBundle::new(&[ciphersuite.name()], &credential_bundle, &crypto, vec![])
+                .unwrap();
+        (credential_bundle, key_package_bundle)
+    };
+    let (alice_credential_bundle, alice_key_package_bundle) = setup_client("Alice");
+    let (_bob_credential_bundle, bob_key_package_bundle) = setup_client("Bob");
+
+    // Alice's Welcome doesn't contain the ratchet tree extension.
+    let mut alice_group = MlsGroup::builder(GroupId::random(&crypto), alice_key_package_bundle)
+        .build(&crypto)
+        .expect("Error creating group.");
+    let bob_add_proposal = alice_group
+        .create_add_proposal(
+            framing_parameters,
+            &alice_credential_bundle,
+            bob_key_package_bundle.key_package().clone(),
+            &crypto,
+        )
+        .expect("Could not create proposal.");
+    let proposal_store = ProposalStore::from_staged_proposal(
+        StagedProposal::from_mls_plaintext(ciphersuite, &crypto, bob_add_proposal)
+            .expect("Could not create StagedProposal."),
+    );
+    let params = CreateCommitParams::builder()
+        .framing_parameters(framing_parameters)
+        .credential_bundle(&alice_credential_bundle)
+        .proposal_store(&proposal_store)
+        .force_self_update(false)
+        .build();
+    let (commit, welcome_option, _kpb_option) = alice_group
+        .create_commit(params, &crypto)
+        .expect("Error creating commit");
+    let staged_commit = alice_group
+        .stage_commit(&commit, &proposal_store, &[], None, &crypto)
+        .expect("Error staging commit");
+    alice_group.merge_commit(staged_commit);
+
+    // Bob requires the tree in the Welcome and doesn't have it otherwise.
+    let error = MlsGroup::new_from_welcome_with_config(
+        welcome_option.expect("Expected a Welcome message."),
+        None,
+        bob_key_package_bundle,
+        None,
+        MlsGroupConfig {
+            require_tree_in_welcome: true,
+            ..MlsGroupConfig::default()
+        },
+        &crypto,
+    )
+    .expect_err("Joining without the required ratchet tree must fail.");
+    assert_eq!(
+        error,
+        MlsGroupError::WelcomeError(WelcomeError::RatchetTreeRequiredButMissing)
+    );
+}
+
+#[test]
+fn test_max_tree_depth() {
+    let crypto = OpenMlsRustCrypto::default();
+    let ciphersuite = &Config::supported_ciphersuites()[0];
+    let framing_parameters = FramingParameters::new(&[], WireFormat::MlsPlaintext);
+
+    let setup_client = |id: &str| {
+        let credential_bundle = CredentialBundle::new(
+            id.into(),
+            CredentialType::Basic,
+            ciphersuite.signature_scheme(),
+            &crypto,
+        )
+        .unwrap();
+        let key_package_bundle =
+            KeyPackageBundle::new(&[ciphersuite.name()], &credential_bundle, &crypto, vec![])
+                .unwrap();
+        (credential_bundle, key_package_bundle)
+    };
+    let (alice_credential_bundle, alice_key_package_bundle) = setup_client("Alice");
+    let (_bob_credential_bundle, bob_key_package_bundle) = setup_client("Bob");
+    let (_charlie_credential_bundle, charlie_key_package_bundle) = setup_client("Charlie");
+
+    // Alice sends the ratchet tree in the Welcome.
+    let mut alice_group = MlsGroup::builder(GroupId::random(&crypto), alice_key_package_bundle)
+        .with_config(MlsGroupConfig {
+            add_ratchet_tree_extension: true,
+            ..MlsGroupConfig::default()
+        })
+        .build(&crypto)
+        .expect("Error creating group.");
+
+    // Alice adds Bob and Charlie, which results in a tree of depth 2.
+    let mut proposal_store = ProposalStore::new();
+    for key_package_bundle in [&bob_key_package_bundle, &charlie_key_package_bundle] {
+        let add_proposal = alice_group
+            .create_add_proposal(
+                framing_parameters,
+                &alice_credential_bundle,
+                key_package_bundle.key_package().clone(),
+                &crypto,
+            )
+            .expect("Could not create proposal.");
+        proposal_store.add(
+            StagedProposal::from_mls_plaintext(ciphersuite, &crypto, add_proposal)
+                .expect("Could not create StagedProposal."),
+        );
+    }
+    let params = CreateCommitParams::builder()
+        .framing_parameters(framing_parameters)
+        .credential_bundle(&alice_credential_bundle)
+        .proposal_store(&proposal_store)
+        .force_self_update(false)
+        .build();
+    let (commit, welcome_option, _kpb_option) = alice_group
+        .create_commit(params, &crypto)
+        .expect("Error creating commit");
+    let staged_commit = alice_group
+        .stage_commit(&commit, &proposal_store, &[], None, &crypto)
+        .expect("Error staging commit");
+    alice_group.merge_commit(staged_commit);
+    let welcome = welcome_option.expect("Expected a Welcome message.");
+
+    // Bob only accepts trees of depth 1.
+    let error = MlsGroup::new_from_welcome_with_config(
+        welcome.clone(),
+        None,
+        bob_key_package_bundle,
+        None,
+        MlsGroupConfig {
+            max_tree_depth: 1,
+            ..MlsGroupConfig::default()
+        },
+        &crypto,
+    )
+    .expect_err("Joining with a tree that is too deep must fail.");
+    assert_eq!(
+        error,
+        MlsGroupError::WelcomeError(WelcomeError::InvalidRatchetTree(TreeError::TreeTooDeep))
+    );
+
+    // Charlie accepts trees of depth 2.
+    MlsGroup::new_from_welcome_with_config(
+        welcome,
+        None,
+        charlie_key_package_bundle,
+        None,
+        MlsGroupConfig {
+            max_tree_depth: 2,
+            ..MlsGroupConfig::default()
+        },
+        &crypto,
+    )
+    .expect("Error joining group from Welcome");
+}
+
+#[test]
+fn test_member_hpke_key() {
+    let crypto = OpenMlsRustCrypto::default();
+    let ciphersuite = &Config::supported_ciphersuites()[0];
+    let framing_parameters = FramingParameters::new(&[], WireFormat::MlsPlaintext);
+
+    let (mut alice_group, mut members) =
+        setup_group_with_members(ciphersuite, &["Alice", "Bob"], &crypto);
+    let (bob_credential_bundle, mut bob_group) = members.remove(0);
+    let bob_index = bob_group.sender_index();
+
+    // Alice sees the key from Bob's key package.
+    let old_key = alice_group
+        .member_hpke_key(bob_index)
+        .expect("Bob is not a member");
+    assert_eq!(&old_key, bob_group.tree().own_key_package().hpke_init_key());
+
+    // === Bob updates ===
+    let proposal_store = ProposalStore::new();
+    let params = CreateCommitParams::builder()
+        .framing_parameters(framing_parameters)
+        .credential_bundle(&bob_credential_bundle)
+        .proposal_store(&proposal_store)
+        .build();
+    let (commit, _welcome_option, kpb_option) = bob_group
+        .create_commit(params, &crypto)
+        .expect("Error creating commit");
+    let key_package_bundle = kpb_option.expect("Expected a KeyPackageBundle.");
+    let new_key = key_package_bundle.key_package().hpke_init_key().clone();
+    let staged_commit = bob_group
+        .stage_commit(
+            &commit,
+            &proposal_store,
+            &[key_package_bundle],
+            None,
+            &crypto,
+        )
+        .expect("Error staging commit");
+    bob_group.merge_commit(staged_commit);
+    let staged_commit = alice_group
+        .stage_commit(&commit, &proposal_store, &[], None, &crypto)
+        .expect("Error staging commit");
+    alice_group.merge_commit(staged_commit);
+
+    // Alice now sees the key from Bob's new key package bundle.
+    let key = alice_group
+        .member_hpke_key(bob_index)
+        .expect("Bob is not a member");
+    assert_eq!(key, new_key);
+    assert_ne!(key, old_key);
+
+    // There is no member outside of the tree.
+    assert_eq!(
+        alice_group
+            .member_hpke_key(LeafIndex::from(2u32))
+            .expect_err("Found a member outside of the tree."),
+        MlsGroupError::UnknownMember
+    );
+}
@@ -0,0 +1,110 @@
+use openmls_rust_crypto::OpenMlsRustCrypto;
+
+use crate::{
+    group::{create_commit_params::CreateCommitParams, policy::GroupPolicy},
+    messages::proposals::{Proposal, RemoveProposal},
+    prelude::*,
+};
+
+/// Alice adds Bob with a key package that expires shortly after. Once it has
+/// expired, a policy that removes expired members must yield exactly one
+/// remove proposal for Bob.
+#[test]
+fn test_remove_expired_members_policy() {
+    let crypto = OpenMlsRustCrypto::default();
+    let ciphersuite = &Config::supported_ciphersuites()[0];
+    let framing_parameters = FramingParameters::new(&[], WireFormat::MlsPlaintext);
+
+    let alice_credential_bundle = CredentialBundle::new(
+        "Alice".into(),
+        CredentialType::Basic,
+        ciphersuite.signature_scheme(),
+        &crypto,
+    )
+    .expect("Could not create credential bundle.");
+    let bob_credential_bundle = CredentialBundle::new(
+        "Bob".into(),
+        CredentialType::Basic,
+        ciphersuite.signature_scheme(),
+        &crypto,
+    )
+    .expect("Could not create credential bundle.");
+
+    let alice_key_package_bundle = KeyPackageBundle::new(
+        &[ciphersuite.name()],
+        &alice_credential_bundle,
+        &crypto,
+        Vec::new(),
+    )
+    .expect("Could not create key package bundle.");
+    // Bob's key package is only valid for one more second.
+    let bob_key_package_bundle = KeyPackageBundle::new(
+        &[ciphersuite.name()],
+        &bob_credential_bundle,
+        &crypto,
+        vec![Extension::LifeTime(LifetimeExtension::new(1))],
+    )
+    .expect("Could not create key package bundle.");
+
+    let mut alice_group = MlsGroup::builder(GroupId::random(&crypto), alice_key_package_bundle)
+        .build(&crypto)
+        .expect("Error creating group.");
+
+    let bob_add_proposal = alice_group
+        .create_add_proposal(
+            framing_parameters,
+            &alice_credential_bundle,
+            bob_key_package_bundle.key_package().clone(),
+            &crypto,
+        )
+        .expect("Could not create proposal.");
+    let proposal_store = ProposalStore::from_staged_proposal(
+        StagedProposal::from_mls_plaintext(ciphersuite, &crypto, bob_add_proposal)
+            .expect("Could not create StagedProposal."),
+    );
+    let params = CreateCommitParams::builder()
+        .framing_parameters(framing_parameters)
+        .credential_bundle(&alice_credential_bundle)
+        .proposal_store(&proposal_store)
+        .force_self_update(false)
+        .build();
+    let (mls_plaintext_commit, _welcome_option, _kpb_option) = alice_group
+        .create_commit(params, &crypto)
+        .expect("Error creating commit");
+    let staged_commit = alice_group
+        .stage_commit(&mls_plaintext_commit, &proposal_store, &[], None, &crypto)
+        .expect("Error staging commit");
+    alice_group.merge_commit(staged_commit);
+
+    let policy = GroupPolicy {
+        remove_expired_members: true,
+    };
+
+    // Wait for Bob's key package to expire.
+    std::thread::sleep(std::time::Duration::from_secs(2));
+
+    let proposals = alice_group
+        .policy_proposals(
+            &policy,
+            framing_parameters,
+            &alice_credential_bundle,
+            &crypto,
+        )
+        .expect("Could not compute policy proposals.");
+    assert_eq!(proposals.len(), 1);
+    assert_eq!(
+        proposals[0].content(),
+        &MlsPlaintextContentType::Proposal(Proposal::Remove(RemoveProposal { removed: 1 }))
+    );
+
+    // A policy without rules doesn't produce any proposals.
+    let proposals = alice_group
+        .policy_proposals(
+            &GroupPolicy::default(),
+            framing_parameters,
+            &alice_credential_bundle,
+            &crypto,
+        )
+        .expect("Could not compute policy proposals.");
+    assert!(proposals.is_empty());
+}
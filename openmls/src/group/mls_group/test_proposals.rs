@@ -1,22 +1,32 @@
 use openmls_rust_crypto::OpenMlsRustCrypto;
 use openmls_traits::OpenMlsCryptoProvider;
+use tls_codec::Deserialize;
 
 use crate::{
     ciphersuite::{Ciphersuite, Secret},
     config::{errors::ConfigError, Config},
     credentials::{CredentialBundle, CredentialType},
-    extensions::{Extension, ExtensionType, KeyIdExtension, RequiredCapabilitiesExtension},
+    extensions::{
+        Extension, ExtensionType, KeyIdExtension, LifetimeExtension, RequiredCapabilitiesExtension,
+    },
     framing::sender::{Sender, SenderType},
-    framing::{FramingParameters, MlsPlaintext},
+    framing::{FramingParameters, MlsPlaintext, MlsPlaintextContentType},
     group::{
         create_commit_params::CreateCommitParams,
-        errors::MlsGroupError,
+        errors::{MlsGroupError, ProposalValidationError, StagedProposalQueueError},
         proposals::{CreationProposalQueue, ProposalStore, StagedProposal, StagedProposalQueue},
-        GroupContext, GroupEpoch, GroupId, WireFormat,
+        GroupContext, GroupEpoch, GroupId, UnknownProposalPolicy, WireFormat,
     },
     key_packages::{KeyPackageBundle, KeyPackageError},
-    messages::proposals::{AddProposal, Proposal, ProposalOrRef, ProposalReference, ProposalType},
+    messages::{
+        proposals::{
+            AddProposal, AppAckProposal, Proposal, ProposalOrRef, ProposalReference, ProposalType,
+            RemoveProposal,
+        },
+        Commit,
+    },
     schedule::MembershipKey,
+    test_utils::setup_group_with_members,
     tree::index::*,
 };
 
@@ -275,6 +285,7 @@ fn proposal_queue_order() {
             proposal_or_refs,
             &proposal_store,
             sender,
+            UnknownProposalPolicy::default(),
         )
         .unwrap();
 
@@ -431,7 +442,7 @@ fn test_group_context_extensions() {
 
     // Make sure that Bob can join the group with the required extension in place
     // and Bob's key package supporting them.
-    let _bob_group = MlsGroup::new_from_welcome(
+    let bob_group = MlsGroup::new_from_welcome(
         welcome_bundle_alice_bob_option.unwrap(),
         Some(ratchet_tree),
         bob_key_package_bundle,
@@ -439,6 +450,16 @@ fn test_group_context_extensions() {
         &crypto,
     )
     .expect("Error joining group.");
+
+    // Alice and Bob must agree on the group context extensions.
+    assert!(alice_group
+        .group_context_extensions()
+        .iter()
+        .any(|e| e.extension_type() == ExtensionType::RequiredCapabilities));
+    assert_eq!(
+        alice_group.group_context_extensions(),
+        bob_group.group_context_extensions()
+    );
 }
 
 #[test]
@@ -689,3 +710,398 @@ fn test_group_context_extension_proposal() {
             .expect("Error exporting secret.")
     )
 }
+
+/// A commit that references a proposal from an external sender must be
+/// rejected, since the group doesn't authorize any external senders.
+#[test]
+fn test_unauthorized_external_proposal() {
+    let crypto = OpenMlsRustCrypto::default();
+    let ciphersuite = &Config::supported_ciphersuites()[0];
+    let framing_parameters = FramingParameters::new(&[], WireFormat::MlsPlaintext);
+
+    let (alice_credential_bundle, alice_key_package_bundle) =
+        setup_client("Alice", ciphersuite, &crypto);
+    let (_bob_credential_bundle, bob_key_package_bundle) =
+        setup_client("Bob", ciphersuite, &crypto);
+
+    let mut alice_group = MlsGroup::builder(GroupId::random(&crypto), alice_key_package_bundle)
+        .build(&crypto)
+        .expect("Error creating MlsGroup.");
+
+    // Pretend the add proposal was sent by an unlisted external sender.
+    let mut bob_add_proposal = alice_group
+        .create_add_proposal(
+            framing_parameters,
+            &alice_credential_bundle,
+            bob_key_package_bundle.key_package().clone(),
+            &crypto,
+        )
+        .expect("Could not create proposal");
+    bob_add_proposal.set_sender(Sender {
+        sender_type: SenderType::Preconfigured,
+        sender: LeafIndex::from(0u32),
+    });
+
+    let proposal_store = ProposalStore::from_staged_proposal(
+        StagedProposal::from_mls_plaintext(ciphersuite, &crypto, bob_add_proposal)
+            .expect("Could not create StagedProposal."),
+    );
+    let params = CreateCommitParams::builder()
+        .framing_parameters(framing_parameters)
+        .credential_bundle(&alice_credential_bundle)
+        .proposal_store(&proposal_store)
+        .force_self_update(false)
+        .build();
+    let (mls_plaintext_commit, _welcome_option, _kpb_option) = alice_group
+        .create_commit(params, &crypto)
+        .expect("Error creating commit");
+
+    let error = alice_group
+        .stage_commit(&mls_plaintext_commit, &proposal_store, &[], None, &crypto)
+        .expect_err("Staging a commit with an unauthorized external proposal must fail.");
+    assert_eq!(
+        error,
+        MlsGroupError::ProposalValidationError(
+            ProposalValidationError::UnauthorizedExternalProposal
+        )
+    );
+}
+
+#[test]
+fn test_update_sender_mismatch() {
+    let crypto = OpenMlsRustCrypto::default();
+    let ciphersuite = &Config::supported_ciphersuites()[0];
+    let framing_parameters = FramingParameters::new(&[], WireFormat::MlsPlaintext);
+
+    let (_alice_group, mut members) =
+        setup_group_with_members(ciphersuite, &["Alice", "Bob", "Charlie"], &crypto);
+    let (bob_credential_bundle, mut bob_group) = members.remove(0);
+    let (charlie_credential_bundle, charlie_group) = members.remove(0);
+
+    // Bob proposes to update his leaf, but the proposal claims to be sent by
+    // Alice.
+    let bob_key_package_bundle = KeyPackageBundle::new(
+        &[ciphersuite.name()],
+        &bob_credential_bundle,
+        &crypto,
+        vec![],
+    )
+    .expect("Could not create key package bundle.");
+    let mut bob_update_proposal = bob_group
+        .create_update_proposal(
+            framing_parameters,
+            &bob_credential_bundle,
+            bob_key_package_bundle.key_package().clone(),
+            &crypto,
+        )
+        .expect("Could not create proposal");
+    bob_update_proposal.set_sender(Sender {
+        sender_type: SenderType::Member,
+        sender: LeafIndex::from(0u32),
+    });
+
+    // Charlie commits the proposal.
+    let proposal_store = ProposalStore::from_staged_proposal(
+        StagedProposal::from_mls_plaintext(ciphersuite, &crypto, bob_update_proposal)
+            .expect("Could not create StagedProposal."),
+    );
+    let params = CreateCommitParams::builder()
+        .framing_parameters(framing_parameters)
+        .credential_bundle(&charlie_credential_bundle)
+        .proposal_store(&proposal_store)
+        .force_self_update(false)
+        .build();
+    let (mls_plaintext_commit, _welcome_option, _kpb_option) = charlie_group
+        .create_commit(params, &crypto)
+        .expect("Error creating commit");
+
+    let error = bob_group
+        .stage_commit(&mls_plaintext_commit, &proposal_store, &[], None, &crypto)
+        .expect_err("Staging a commit with a mismatched update proposal must fail.");
+    assert_eq!(
+        error,
+        MlsGroupError::ProposalValidationError(ProposalValidationError::UpdateSenderMismatch)
+    );
+}
+
+/// Committed proposals of an unsupported type either reject the commit or are
+/// skipped, depending on the [`UnknownProposalPolicy`].
+#[test]
+fn test_unknown_proposal_policy() {
+    let crypto = OpenMlsRustCrypto::default();
+    let ciphersuite = &Config::supported_ciphersuites()[0];
+
+    let (_bob_credential_bundle, bob_key_package_bundle) =
+        setup_client("Bob", ciphersuite, &crypto);
+
+    // AppAck proposals are not supported, so they stand in for a proposal
+    // type that is unknown to this implementation.
+    assert!(!ProposalType::AppAck.is_supported());
+    let app_ack_proposal = AppAckProposal::tls_deserialize(&mut &[0u8; 4][..])
+        .expect("Could not decode AppAck proposal.");
+    let proposal_or_refs = vec![
+        ProposalOrRef::Proposal(Proposal::Add(AddProposal {
+            key_package: bob_key_package_bundle.key_package().clone(),
+        })),
+        ProposalOrRef::Proposal(Proposal::AppAck(app_ack_proposal)),
+    ];
+    let sender = Sender {
+        sender_type: SenderType::Member,
+        sender: LeafIndex::from(0u32),
+    };
+
+    // The commit is rejected by default.
+    assert_eq!(
+        UnknownProposalPolicy::default(),
+        UnknownProposalPolicy::Reject
+    );
+    let result = StagedProposalQueue::from_committed_proposals(
+        ciphersuite,
+        &crypto,
+        proposal_or_refs.clone(),
+        &ProposalStore::new(),
+        sender,
+        UnknownProposalPolicy::Reject,
+    );
+    assert!(matches!(
+        result,
+        Err(StagedProposalQueueError::UnsupportedProposalType)
+    ));
+
+    // Otherwise the unsupported proposal is skipped.
+    let proposal_queue = StagedProposalQueue::from_committed_proposals(
+        ciphersuite,
+        &crypto,
+        proposal_or_refs,
+        &ProposalStore::new(),
+        sender,
+        UnknownProposalPolicy::Ignore,
+    )
+    .expect("Error building the proposal queue.");
+    assert_eq!(
+        proposal_queue.filtered_by_type(ProposalType::Add).count(),
+        1
+    );
+    assert_eq!(
+        proposal_queue
+            .filtered_by_type(ProposalType::AppAck)
+            .count(),
+        0
+    );
+}
+
+/// A commit that removes every member of the group, including the committer
+/// itself, must be rejected.
+#[test]
+fn test_commit_emptying_group() {
+    let crypto = OpenMlsRustCrypto::default();
+    let ciphersuite = &Config::supported_ciphersuites()[0];
+    let framing_parameters = FramingParameters::new(&[], WireFormat::MlsPlaintext);
+
+    let (mut alice_group, mut members) =
+        setup_group_with_members(ciphersuite, &["Alice", "Bob", "Charlie"], &crypto);
+    let (bob_credential_bundle, bob_group) = members.remove(0);
+
+    // Bob commits to removing Alice and Charlie. `create_commit` doesn't allow
+    // Bob to remove himself, so we replace the content of the commit
+    // afterwards.
+    let remove = |removed: u32| Proposal::Remove(RemoveProposal { removed });
+    let params = CreateCommitParams::builder()
+        .framing_parameters(framing_parameters)
+        .credential_bundle(&bob_credential_bundle)
+        .proposal_store(&ProposalStore::new())
+        .inline_proposals(vec![remove(0), remove(2)])
+        .force_self_update(false)
+        .build();
+    let (mut mls_plaintext_commit, _welcome_option, _kpb_option) = bob_group
+        .create_commit(params, &crypto)
+        .expect("Error creating commit");
+
+    // Bob leaves in the same commit.
+    mls_plaintext_commit.set_content(MlsPlaintextContentType::Commit(Commit {
+        proposals: vec![
+            ProposalOrRef::Proposal(remove(0)),
+            ProposalOrRef::Proposal(remove(2)),
+            ProposalOrRef::Proposal(remove(1)),
+        ]
+        .into(),
+        path: None,
+    }));
+
+    let error = alice_group
+        .stage_commit(
+            &mls_plaintext_commit,
+            &ProposalStore::new(),
+            &[],
+            None,
+            &crypto,
+        )
+        .expect_err("Staging a commit that empties the group must fail.");
+    assert_eq!(
+        error,
+        MlsGroupError::ProposalValidationError(ProposalValidationError::WouldEmptyGroup)
+    );
+}
+
+/// A new member can inspect the required capabilities of a group in the
+/// Welcome before joining it.
+#[test]
+fn test_inspect_welcome() {
+    let crypto = OpenMlsRustCrypto::default();
+    let ciphersuite = &Config::supported_ciphersuites()[0];
+    let framing_parameters = FramingParameters::new(&[], WireFormat::MlsPlaintext);
+
+    let (alice_credential_bundle, alice_key_package_bundle) =
+        setup_client("Alice", ciphersuite, &crypto);
+    let (bob_credential_bundle, _) = setup_client("Bob", ciphersuite, &crypto);
+    let bob_key_package_bundle = KeyPackageBundle::new(
+        &[ciphersuite.name()],
+        &bob_credential_bundle,
+        &crypto,
+        vec![Extension::KeyPackageId(KeyIdExtension::default())],
+    )
+    .unwrap();
+
+    let required_capabilities = RequiredCapabilitiesExtension::new(
+        &[ExtensionType::Capabilities, ExtensionType::KeyId],
+        &[
+            ProposalType::GroupContextExtensions,
+            ProposalType::Add,
+            ProposalType::Remove,
+            ProposalType::Update,
+        ],
+    );
+    let mut alice_group = MlsGroup::builder(GroupId::random(&crypto), alice_key_package_bundle)
+        .with_required_capabilities(required_capabilities.clone())
+        .build(&crypto)
+        .expect("Error creating MlsGroup.");
+
+    // Alice adds Bob
+    let bob_add_proposal = alice_group
+        .create_add_proposal(
+            framing_parameters,
+            &alice_credential_bundle,
+            bob_key_package_bundle.key_package().clone(),
+            &crypto,
+        )
+        .expect("Could not create proposal");
+    let proposal_store = ProposalStore::from_staged_proposal(
+        StagedProposal::from_mls_plaintext(ciphersuite, &crypto, bob_add_proposal)
+            .expect("Could not create StagedProposal."),
+    );
+    let params = CreateCommitParams::builder()
+        .framing_parameters(framing_parameters)
+        .credential_bundle(&alice_credential_bundle)
+        .proposal_store(&proposal_store)
+        .force_self_update(false)
+        .build();
+    let (mls_plaintext_commit, welcome_option, _kpb_option) = alice_group
+        .create_commit(params, &crypto)
+        .expect("Error creating commit");
+    let staged_commit = alice_group
+        .stage_commit(&mls_plaintext_commit, &proposal_store, &[], None, &crypto)
+        .expect("error staging commit");
+    alice_group.merge_commit(staged_commit);
+    let welcome = welcome_option.expect("Expected a Welcome message.");
+
+    // Bob inspects the Welcome without joining the group.
+    let group_info = welcome
+        .inspect_group_info(&bob_key_package_bundle, None, &crypto)
+        .expect("Error inspecting the Welcome.");
+    assert_eq!(group_info.group_id(), alice_group.group_id());
+    assert_eq!(group_info.epoch(), alice_group.context().epoch());
+    assert_eq!(
+        group_info.group_context_extensions(),
+        alice_group.group_context_extensions()
+    );
+    assert_eq!(
+        group_info.required_capabilities(),
+        Some(&required_capabilities)
+    );
+    assert!(!bob_key_package_bundle.is_consumed(&crypto));
+
+    // Bob then joins the group.
+    let bob_group = MlsGroup::new_from_welcome(
+        welcome,
+        Some(alice_group.tree().public_key_tree_copy()),
+        bob_key_package_bundle,
+        None,
+        &crypto,
+    )
+    .expect("Error joining group.");
+    assert_eq!(
+        bob_group.group_context_extensions(),
+        alice_group.group_context_extensions()
+    );
+}
+
+/// Validating a set of proposals that violates several rules reports all of
+/// the violations instead of only the first one.
+#[test]
+fn test_validate_proposals() {
+    let crypto = OpenMlsRustCrypto::default();
+    let ciphersuite = &Config::supported_ciphersuites()[0];
+
+    let (_alice_credential_bundle, alice_key_package_bundle) =
+        setup_client("Alice", ciphersuite, &crypto);
+    let (charlie_credential_bundle, charlie_key_package_bundle) =
+        setup_client("Charlie", ciphersuite, &crypto);
+    let (_dave_credential_bundle, dave_key_package_bundle) =
+        setup_client("Dave", ciphersuite, &crypto);
+
+    let alice_group = MlsGroup::builder(GroupId::random(&crypto), alice_key_package_bundle)
+        .build(&crypto)
+        .expect("Error creating MlsGroup.");
+
+    // A second key package of Charlie that has already expired.
+    let charlie_expired_key_package_bundle = KeyPackageBundle::new(
+        &[ciphersuite.name()],
+        &charlie_credential_bundle,
+        &crypto,
+        vec![Extension::LifeTime(LifetimeExtension::new(0))],
+    )
+    .expect("Could not create key package bundle.");
+    std::thread::sleep(std::time::Duration::from_millis(1));
+
+    let add_proposal = |key_package_bundle: &KeyPackageBundle, sender_type: SenderType| {
+        StagedProposal::from_proposal_and_sender(
+            ciphersuite,
+            &crypto,
+            Proposal::Add(AddProposal {
+                key_package: key_package_bundle.key_package().clone(),
+            }),
+            Sender {
+                sender_type,
+                sender: LeafIndex::from(0u32),
+            },
+        )
+        .expect("Could not create StagedProposal.")
+    };
+
+    // A valid set of proposals yields an empty report.
+    let report = alice_group.validate_proposals(&[add_proposal(
+        &charlie_key_package_bundle,
+        SenderType::Member,
+    )]);
+    assert!(report.is_valid());
+
+    // Charlie is added twice, once with an expired key package, and Dave is
+    // added by an unauthorized external sender.
+    let report = alice_group.validate_proposals(&[
+        add_proposal(&charlie_key_package_bundle, SenderType::Member),
+        add_proposal(&charlie_expired_key_package_bundle, SenderType::Member),
+        add_proposal(&dave_key_package_bundle, SenderType::Preconfigured),
+    ]);
+    assert!(!report.is_valid());
+    for violation in [
+        ProposalValidationError::DuplicateIdentityAddProposal,
+        ProposalValidationError::ExpiredKeyPackageAddProposal,
+        ProposalValidationError::UnauthorizedExternalProposal,
+    ] {
+        assert!(
+            report.violations().contains(&violation),
+            "{:?} is missing in the report",
+            violation
+        );
+    }
+}
@@ -0,0 +1,346 @@
+//! # GroupInfo signature and validation unit tests
+use super::*;
+
+use crate::{messages::GroupSecrets, prelude::*, schedule::KeySchedule};
+use mls_group::create_commit_params::CreateCommitParams;
+use openmls_rust_crypto::OpenMlsRustCrypto;
+use openmls_traits::crypto::OpenMlsCrypto;
+use tls_codec::Deserialize;
+
+/// Alice creates a group with Bob. Returns the Welcome for Bob, Bob's
+/// credential and key package bundles, as well as Alice's ratchet tree.
+fn setup_welcome(
+    ciphersuite: &'static Ciphersuite,
+    backend: &impl OpenMlsCryptoProvider,
+) -> (
+    Welcome,
+    CredentialBundle,
+    KeyPackageBundle,
+    Vec<Option<Node>>,
+) {
+    let framing_parameters = FramingParameters::new(&[], WireFormat::MlsPlaintext);
+    let alice_credential_bundle = CredentialBundle::new(
+        "Alice".into(),
+        CredentialType::Basic,
+        ciphersuite.signature_scheme(),
+        backend,
+    )
+    .unwrap();
+    let bob_credential_bundle = CredentialBundle::new(
+        "Bob".into(),
+        CredentialType::Basic,
+        ciphersuite.signature_scheme(),
+        backend,
+    )
+    .unwrap();
+    let alice_key_package_bundle = KeyPackageBundle::new(
+        &[ciphersuite.name()],
+        &alice_credential_bundle,
+        backend,
+        Vec::new(),
+    )
+    .unwrap();
+    let bob_key_package_bundle = KeyPackageBundle::new(
+        &[ciphersuite.name()],
+        &bob_credential_bundle,
+        backend,
+        Vec::new(),
+    )
+    .unwrap();
+
+    let mut alice_group = MlsGroup::builder(GroupId::random(backend), alice_key_package_bundle)
+        .build(backend)
+        .expect("Error creating group.");
+    let bob_add_proposal = alice_group
+        .create_add_proposal(
+            framing_parameters,
+            &alice_credential_bundle,
+            bob_key_package_bundle.key_package().clone(),
+            backend,
+        )
+        .expect("Could not create proposal.");
+    let proposal_store = ProposalStore::from_staged_proposal(
+        StagedProposal::from_mls_plaintext(ciphersuite, backend, bob_add_proposal)
+            .expect("Could not create StagedProposal."),
+    );
+    let params = CreateCommitParams::builder()
+        .framing_parameters(framing_parameters)
+        .credential_bundle(&alice_credential_bundle)
+        .proposal_store(&proposal_store)
+        .force_self_update(false)
+        .build();
+    let (commit, welcome_option, _kpb_option) = alice_group
+        .create_commit(params, backend)
+        .expect("Error creating commit");
+    let staged_commit = alice_group
+        .stage_commit(&commit, &proposal_store, &[], None, backend)
+        .expect("Error staging commit");
+    alice_group.merge_commit(staged_commit);
+    let ratchet_tree = alice_group.tree().public_key_tree_copy();
+
+    (
+        welcome_option.expect("Expected a Welcome message."),
+        bob_credential_bundle,
+        bob_key_package_bundle,
+        ratchet_tree,
+    )
+}
+
+/// Decrypt the `GroupInfo` in the `welcome`, apply `tamper` to it and encrypt
+/// the result again.
+fn tamper_with_group_info(
+    welcome: &mut Welcome,
+    key_package_bundle: &KeyPackageBundle,
+    ciphersuite: &'static Ciphersuite,
+    backend: &impl OpenMlsCryptoProvider,
+    tamper: impl FnOnce(GroupInfo) -> GroupInfo,
+) {
+    let egs = MlsGroup::find_key_package_from_welcome_secrets(
+        key_package_bundle.key_package(),
+        welcome.secrets(),
+        backend,
+    )
+    .expect("JoinerSecret not found");
+    let group_secrets_bytes = backend
+        .crypto()
+        .hpke_open(
+            ciphersuite.hpke_config(),
+            &egs.encrypted_group_secrets,
+            key_package_bundle.private_key().as_slice(),
+            &[],
+            &[],
+        )
+        .expect("Could not decrypt group secrets");
+    let group_secrets = GroupSecrets::tls_deserialize(&mut group_secrets_bytes.as_slice())
+        .expect("Could not decode GroupSecrets")
+        .config(ciphersuite, ProtocolVersion::default())
+        .expect("Malformed GroupSecrets");
+
+    let key_schedule = KeySchedule::init(
+        ciphersuite,
+        backend,
+        group_secrets.joiner_secret,
+        psk_output(ciphersuite, backend, None, &group_secrets.psks)
+            .expect("Could not extract PSKs"),
+    );
+    let (welcome_key, welcome_nonce) = key_schedule
+        .welcome(backend)
+        .expect("Expected a WelcomeSecret")
+        .derive_welcome_key_nonce(backend);
+
+    let group_info_bytes = welcome_key
+        .aead_open(backend, welcome.encrypted_group_info(), &[], &welcome_nonce)
+        .expect("Could not decrypt GroupInfo");
+    let group_info = GroupInfo::tls_deserialize(&mut group_info_bytes.as_slice())
+        .expect("Could not decode GroupInfo");
+
+    let group_info = tamper(group_info);
+
+    let encrypted_group_info = welcome_key
+        .aead_seal(
+            backend,
+            &group_info
+                .tls_serialize_detached()
+                .expect("Could not encode GroupInfo"),
+            &[],
+            &welcome_nonce,
+        )
+        .unwrap();
+    welcome.set_encrypted_group_info(encrypted_group_info);
+}
+
+/// A well-formed signature from the wrong key fails verification.
+#[test]
+fn wrong_key_group_info_signature() {
+    let crypto = OpenMlsRustCrypto::default();
+    for ciphersuite in Config::supported_ciphersuites() {
+        let (mut welcome, bob_credential_bundle, bob_key_package_bundle, ratchet_tree) =
+            setup_welcome(ciphersuite, &crypto);
+
+        // Bob signs the GroupInfo instead of Alice.
+        tamper_with_group_info(
+            &mut welcome,
+            &bob_key_package_bundle,
+            ciphersuite,
+            &crypto,
+            |group_info| {
+                group_info
+                    .re_sign(&bob_credential_bundle, &crypto)
+                    .expect("Error re-signing GroupInfo")
+            },
+        );
+
+        let error = MlsGroup::new_from_welcome(
+            welcome,
+            Some(ratchet_tree),
+            bob_key_package_bundle,
+            None,
+            &crypto,
+        )
+        .expect_err("Joining with a GroupInfo signed by the wrong key must fail.");
+        assert_eq!(
+            error,
+            MlsGroupError::WelcomeError(WelcomeError::InvalidGroupInfoSignature)
+        );
+    }
+}
+
+/// A truncated signature is rejected as malformed before verification.
+#[test]
+fn truncated_group_info_signature() {
+    let crypto = OpenMlsRustCrypto::default();
+    for ciphersuite in Config::supported_ciphersuites() {
+        let (mut welcome, _bob_credential_bundle, bob_key_package_bundle, ratchet_tree) =
+            setup_welcome(ciphersuite, &crypto);
+
+        tamper_with_group_info(
+            &mut welcome,
+            &bob_key_package_bundle,
+            ciphersuite,
+            &crypto,
+            |mut group_info| {
+                let mut signature = group_info.signature().clone();
+                signature.modify(&group_info.signature().as_slice()[..4]);
+                group_info.set_signature(signature);
+                group_info
+            },
+        );
+
+        let error = MlsGroup::new_from_welcome(
+            welcome,
+            Some(ratchet_tree),
+            bob_key_package_bundle,
+            None,
+            &crypto,
+        )
+        .expect_err("Joining with a truncated GroupInfo signature must fail.");
+        assert_eq!(
+            error,
+            MlsGroupError::WelcomeError(WelcomeError::MalformedSignature)
+        );
+    }
+}
+
+/// A signer index that doesn't refer to a leaf in the tree is rejected. The
+/// signer index is a leaf index, so it can only miss a leaf by pointing past
+/// the end of the tree.
+#[test]
+fn signer_not_a_leaf() {
+    let crypto = OpenMlsRustCrypto::default();
+    for ciphersuite in Config::supported_ciphersuites() {
+        let (mut welcome, _bob_credential_bundle, bob_key_package_bundle, ratchet_tree) =
+            setup_welcome(ciphersuite, &crypto);
+        let leaf_count = ratchet_tree.len() as u32 / 2 + 1;
+
+        tamper_with_group_info(
+            &mut welcome,
+            &bob_key_package_bundle,
+            ciphersuite,
+            &crypto,
+            |mut group_info| {
+                group_info.set_signer_index(LeafIndex::from(leaf_count));
+                group_info
+            },
+        );
+
+        let error = MlsGroup::new_from_welcome(
+            welcome,
+            Some(ratchet_tree),
+            bob_key_package_bundle,
+            None,
+            &crypto,
+        )
+        .expect_err("Joining with a signer index outside the tree must fail.");
+        assert_eq!(
+            error,
+            MlsGroupError::WelcomeError(WelcomeError::SignerNotALeaf)
+        );
+    }
+}
+
+/// A confirmed transcript hash that doesn't have the length of the
+/// ciphersuite's hash is rejected before the GroupInfo is used.
+#[test]
+fn wrong_length_confirmed_transcript_hash() {
+    let crypto = OpenMlsRustCrypto::default();
+    for ciphersuite in Config::supported_ciphersuites() {
+        let (mut welcome, _bob_credential_bundle, bob_key_package_bundle, ratchet_tree) =
+            setup_welcome(ciphersuite, &crypto);
+
+        tamper_with_group_info(
+            &mut welcome,
+            &bob_key_package_bundle,
+            ciphersuite,
+            &crypto,
+            |mut group_info| {
+                let mut confirmed_transcript_hash = group_info.confirmed_transcript_hash().to_vec();
+                confirmed_transcript_hash.push(0);
+                group_info.set_confirmed_transcript_hash(confirmed_transcript_hash);
+                group_info
+            },
+        );
+
+        let error = MlsGroup::new_from_welcome(
+            welcome,
+            Some(ratchet_tree),
+            bob_key_package_bundle,
+            None,
+            &crypto,
+        )
+        .expect_err("Joining with a malformed confirmed transcript hash must fail.");
+        assert_eq!(
+            error,
+            MlsGroupError::WelcomeError(WelcomeError::MalformedGroupInfo)
+        );
+    }
+}
+
+/// A signer key package with duplicate extensions is rejected before its
+/// credential is used to verify the GroupInfo signature.
+#[test]
+fn signer_key_package_with_duplicate_extensions() {
+    let crypto = OpenMlsRustCrypto::default();
+    for ciphersuite in Config::supported_ciphersuites() {
+        let (mut welcome, _bob_credential_bundle, bob_key_package_bundle, mut ratchet_tree) =
+            setup_welcome(ciphersuite, &crypto);
+
+        // Alice created the group, so she is the signer in the first leaf.
+        let alice_key_package = ratchet_tree[0]
+            .as_mut()
+            .and_then(|node| node.key_package.as_mut())
+            .expect("Alice's leaf is blank.");
+        let mut extensions = alice_key_package.extensions().to_vec();
+        extensions.push(extensions[0].clone());
+        alice_key_package.set_extensions(extensions);
+
+        // Make the tree hash in the GroupInfo match the tampered tree.
+        let mut tree =
+            RatchetTree::new_from_nodes(&crypto, bob_key_package_bundle.clone(), &ratchet_tree)
+                .expect("Error building tree");
+        tree.canonicalize();
+        let tree_hash = tree.tree_hash(&crypto);
+        tamper_with_group_info(
+            &mut welcome,
+            &bob_key_package_bundle,
+            ciphersuite,
+            &crypto,
+            |mut group_info| {
+                group_info.set_tree_hash(tree_hash);
+                group_info
+            },
+        );
+
+        let error = MlsGroup::new_from_welcome(
+            welcome,
+            Some(ratchet_tree),
+            bob_key_package_bundle,
+            None,
+            &crypto,
+        )
+        .expect_err("Joining with a malformed signer key package must fail.");
+        assert_eq!(
+            error,
+            MlsGroupError::WelcomeError(WelcomeError::InvalidKeyPackage)
+        );
+    }
+}
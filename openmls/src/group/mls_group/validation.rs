@@ -3,7 +3,10 @@
 
 use std::collections::HashSet;
 
-use super::{proposals::StagedProposalQueue, *};
+use super::{
+    proposals::{StagedProposal, StagedProposalQueue},
+    *,
+};
 
 impl MlsGroup {
     // === Messages ===
@@ -77,6 +80,19 @@ impl MlsGroup {
 
     // === Proposals ===
 
+    /// Validate the senders of all committed proposals.
+    ///
+    /// A proposal from an external (preconfigured) sender may only be
+    /// committed if the group authorizes that sender. OpenMLS doesn't support
+    /// an `ExternalSenders` extension yet, so no external sender is authorized
+    /// and all such proposals are rejected.
+    pub fn validate_proposal_senders(
+        &self,
+        staged_proposal_queue: &StagedProposalQueue,
+    ) -> Result<(), MlsGroupError> {
+        first_violation(self.proposal_sender_violations(staged_proposal_queue))
+    }
+
     /// Validate Add proposals. This function implements the following checks:
     ///  - ValSem100
     ///  - ValSem101
@@ -84,47 +100,114 @@ impl MlsGroup {
     ///  - ValSem103
     ///  - ValSem104
     ///  - ValSem105
-    ///  - TODO: ValSem106
+    ///  - ValSem106
     pub fn validate_add_proposals(
         &self,
         staged_proposal_queue: &StagedProposalQueue,
     ) -> Result<(), MlsGroupError> {
+        first_violation(self.add_proposal_violations(staged_proposal_queue))
+    }
+
+    /// Validate Remove proposals. This function implements the following checks:
+    ///  - ValSem107
+    ///  - ValSem108
+    pub fn validate_remove_proposals(
+        &self,
+        staged_proposal_queue: &StagedProposalQueue,
+    ) -> Result<(), MlsGroupError> {
+        first_violation(self.remove_proposal_violations(staged_proposal_queue))
+    }
+
+    /// Validate that at least one member is left in the group after the Add
+    /// and Remove proposals have been applied. This includes the case where
+    /// the committer removes all other members as well as itself.
+    pub fn validate_group_not_emptied(
+        &self,
+        staged_proposal_queue: &StagedProposalQueue,
+    ) -> Result<(), MlsGroupError> {
+        first_violation(self.emptied_group_violations(staged_proposal_queue))
+    }
+
+    /// Validate Update proposals. This function implements the following checks:
+    ///  - The sender is the member whose leaf is updated
+    ///  - ValSem109
+    ///  - ValSem110
+    pub fn validate_update_proposals(
+        &self,
+        staged_proposal_queue: &StagedProposalQueue,
+    ) -> Result<(), MlsGroupError> {
+        first_violation(self.update_proposal_violations(staged_proposal_queue))
+    }
+
+    /// Run all proposal validation rules on `proposals` and collect every
+    /// violation in a [`ProposalValidationReport`], e.g. so that a server can
+    /// tell a committer everything that is wrong with its proposals at once.
+    /// `stage_commit()` runs the same rules, but stops at the first violation.
+    ///
+    /// In addition, the report lists Add proposals with a key package whose
+    /// lifetime is not valid.
+    pub fn validate_proposals(&self, proposals: &[StagedProposal]) -> ProposalValidationReport {
+        let mut staged_proposal_queue = StagedProposalQueue::default();
+        for proposal in proposals {
+            staged_proposal_queue.add(proposal.clone());
+        }
+
+        let mut violations = self.proposal_sender_violations(&staged_proposal_queue);
+        violations.extend(self.add_proposal_violations(&staged_proposal_queue));
+        violations.extend(self.expired_add_proposal_violations(&staged_proposal_queue));
+        violations.extend(self.remove_proposal_violations(&staged_proposal_queue));
+        violations.extend(self.emptied_group_violations(&staged_proposal_queue));
+        violations.extend(self.update_proposal_violations(&staged_proposal_queue));
+
+        ProposalValidationReport { violations }
+    }
+
+    fn proposal_sender_violations(
+        &self,
+        staged_proposal_queue: &StagedProposalQueue,
+    ) -> Vec<ProposalValidationError> {
+        let mut violations = Vec::new();
+        for staged_proposal in staged_proposal_queue.staged_proposals() {
+            if staged_proposal.sender().sender_type == SenderType::Preconfigured {
+                violations.push(ProposalValidationError::UnauthorizedExternalProposal);
+            }
+        }
+        violations
+    }
+
+    fn add_proposal_violations(
+        &self,
+        staged_proposal_queue: &StagedProposalQueue,
+    ) -> Vec<ProposalValidationError> {
         let add_proposals = staged_proposal_queue.add_proposals();
 
+        let mut violations = Vec::new();
         let mut identity_set = HashSet::new();
         let mut signature_key_set = HashSet::new();
         let mut public_key_set = HashSet::new();
         for add_proposal in add_proposals {
-            let identity = add_proposal
-                .add_proposal()
-                .key_package()
-                .credential()
-                .identity()
-                .to_vec();
+            let key_package = add_proposal.add_proposal().key_package();
+            let identity = key_package.credential().identity().to_vec();
             // ValSem100
             if !identity_set.insert(identity) {
-                return Err(ProposalValidationError::DuplicateIdentityAddProposal.into());
+                violations.push(ProposalValidationError::DuplicateIdentityAddProposal);
             }
-            let signature_key = add_proposal
-                .add_proposal()
-                .key_package()
-                .credential()
-                .signature_key()
-                .as_slice()
-                .to_vec();
+            let signature_key = key_package.credential().signature_key().as_slice().to_vec();
             // ValSem101
             if !signature_key_set.insert(signature_key) {
-                return Err(ProposalValidationError::DuplicateSignatureKeyAddProposal.into());
+                violations.push(ProposalValidationError::DuplicateSignatureKeyAddProposal);
             }
-            let public_key = add_proposal
-                .add_proposal()
-                .key_package()
-                .hpke_init_key()
-                .as_slice()
-                .to_vec();
+            let public_key = key_package.hpke_init_key().as_slice().to_vec();
             // ValSem102
             if !public_key_set.insert(public_key) {
-                return Err(ProposalValidationError::DuplicatePublicKeyAddProposal.into());
+                violations.push(ProposalValidationError::DuplicatePublicKeyAddProposal);
+            }
+            // ValSem106
+            if key_package
+                .validate_required_capabilities(self.required_capabilities())
+                .is_err()
+            {
+                violations.push(ProposalValidationError::UnsupportedCapabilitiesAddProposal);
             }
         }
 
@@ -132,32 +215,48 @@ impl MlsGroup {
             let identity = key_package.credential().identity();
             // ValSem103
             if identity_set.contains(identity) {
-                return Err(ProposalValidationError::ExistingIdentityAddProposal.into());
+                violations.push(ProposalValidationError::ExistingIdentityAddProposal);
             }
             // ValSem104
             let signature_key = key_package.credential().signature_key().as_slice();
             if signature_key_set.contains(signature_key) {
-                return Err(ProposalValidationError::ExistingSignatureKeyAddProposal.into());
+                violations.push(ProposalValidationError::ExistingSignatureKeyAddProposal);
             }
             // ValSem105
             let public_key = key_package.hpke_init_key().as_slice();
             if public_key_set.contains(public_key) {
-                return Err(ProposalValidationError::ExistingPublicKeyAddProposal.into());
+                violations.push(ProposalValidationError::ExistingPublicKeyAddProposal);
             }
         }
-        // TODO #538: ValSem106: Check the required capabilities of the add proposals
-        Ok(())
+        violations
     }
 
-    /// Validate Remove proposals. This function implements the following checks:
-    ///  - ValSem107
-    ///  - ValSem108
-    pub fn validate_remove_proposals(
+    fn expired_add_proposal_violations(
         &self,
         staged_proposal_queue: &StagedProposalQueue,
-    ) -> Result<(), MlsGroupError> {
+    ) -> Vec<ProposalValidationError> {
+        staged_proposal_queue
+            .add_proposals()
+            .filter(|add_proposal| {
+                !add_proposal
+                    .add_proposal()
+                    .key_package()
+                    .extension_with_type(ExtensionType::Lifetime)
+                    .and_then(|extension| extension.as_lifetime_extension().ok())
+                    .map(|lifetime| lifetime.is_valid())
+                    .unwrap_or(false)
+            })
+            .map(|_| ProposalValidationError::ExpiredKeyPackageAddProposal)
+            .collect()
+    }
+
+    fn remove_proposal_violations(
+        &self,
+        staged_proposal_queue: &StagedProposalQueue,
+    ) -> Vec<ProposalValidationError> {
         let remove_proposals = staged_proposal_queue.remove_proposals();
 
+        let mut violations = Vec::new();
         let mut removes_set = HashSet::new();
         let tree = &self.tree();
 
@@ -168,51 +267,86 @@ impl MlsGroup {
             let removed = remove_proposal.remove_proposal().removed();
             // ValSem107
             if !removes_set.insert(removed) {
-                return Err(ProposalValidationError::DuplicateMemberRemoval.into());
+                violations.push(ProposalValidationError::DuplicateMemberRemoval);
             }
 
             // ValSem108
             if !index_set.contains(&NodeIndex::from(LeafIndex::from(removed))) {
-                return Err(ProposalValidationError::UnknownMemberRemoval.into());
+                violations.push(ProposalValidationError::UnknownMemberRemoval);
             }
         }
+        violations
+    }
 
-        Ok(())
+    fn emptied_group_violations(
+        &self,
+        staged_proposal_queue: &StagedProposalQueue,
+    ) -> Vec<ProposalValidationError> {
+        let tree = &self.tree();
+        let index_set: HashSet<NodeIndex> =
+            HashSet::from_iter(tree.indexed_key_packages().map(|(index, _kp)| index));
+        let adds = staged_proposal_queue.add_proposals().count();
+        // Only count distinct removals of existing members, so that invalid
+        // Remove proposals don't make the group look empty.
+        let removed: HashSet<NodeIndex> = staged_proposal_queue
+            .remove_proposals()
+            .map(|remove_proposal| {
+                NodeIndex::from(LeafIndex::from(remove_proposal.remove_proposal().removed()))
+            })
+            .filter(|index| index_set.contains(index))
+            .collect();
+
+        if index_set.len() + adds <= removed.len() {
+            vec![ProposalValidationError::WouldEmptyGroup]
+        } else {
+            Vec::new()
+        }
     }
 
-    /// Validate Update proposals. This function implements the following checks:
-    ///  - ValSem109
-    ///  - ValSem110
-    pub fn validate_update_proposals(
+    fn update_proposal_violations(
         &self,
         staged_proposal_queue: &StagedProposalQueue,
-    ) -> Result<(), MlsGroupError> {
+    ) -> Vec<ProposalValidationError> {
         let update_proposals = staged_proposal_queue.update_proposals();
         let tree = &self.tree();
-        let mut indexed_key_packages = tree.indexed_key_packages();
 
+        let mut violations = Vec::new();
         let mut public_key_set = HashSet::new();
-        for key_package in self.tree().key_packages() {
+        for key_package in tree.key_packages() {
             let public_key = key_package.hpke_init_key().as_slice().to_vec();
             public_key_set.insert(public_key);
         }
 
         for update_proposal in update_proposals {
-            if let Some(existing_key_package) = indexed_key_packages
-                .find(|(index, _key_package)| {
-                    &NodeIndex::from(update_proposal.sender().sender) == index
-                })
+            // An Update proposal implicitly updates the sender's leaf, so it
+            // has to come from a member and must not carry the credential of
+            // another member.
+            let sender = update_proposal.sender();
+            if sender.sender_type != SenderType::Member {
+                violations.push(ProposalValidationError::UpdateSenderMismatch);
+                continue;
+            }
+            let identity = update_proposal
+                .update_proposal()
+                .key_package()
+                .credential()
+                .identity();
+            if tree.indexed_key_packages().any(|(index, key_package)| {
+                index != NodeIndex::from(sender.sender)
+                    && key_package.credential().identity() == identity
+            }) {
+                violations.push(ProposalValidationError::UpdateSenderMismatch);
+                continue;
+            }
+
+            if let Some(existing_key_package) = tree
+                .indexed_key_packages()
+                .find(|(index, _key_package)| &NodeIndex::from(sender.sender) == index)
                 .map(|(_index, key_package)| key_package)
             {
                 // ValSem109
-                if update_proposal
-                    .update_proposal()
-                    .key_package()
-                    .credential()
-                    .identity()
-                    != existing_key_package.credential().identity()
-                {
-                    return Err(ProposalValidationError::UpdateProposalIdentityMismatch.into());
+                if identity != existing_key_package.credential().identity() {
+                    violations.push(ProposalValidationError::UpdateProposalIdentityMismatch);
                 }
                 let public_key = update_proposal
                     .update_proposal()
@@ -221,13 +355,39 @@ impl MlsGroup {
                     .as_slice();
                 // ValSem110
                 if public_key_set.contains(public_key) {
-                    return Err(ProposalValidationError::ExistingPublicKeyUpdateProposal.into());
+                    violations.push(ProposalValidationError::ExistingPublicKeyUpdateProposal);
                 }
             } else {
-                return Err(ProposalValidationError::UnknownMember.into());
+                violations.push(ProposalValidationError::UnknownMember);
             }
         }
+        violations
+    }
+}
 
-        Ok(())
+/// Returns the first of the `violations` as an error, if there is one.
+fn first_violation(violations: Vec<ProposalValidationError>) -> Result<(), MlsGroupError> {
+    match violations.into_iter().next() {
+        Some(violation) => Err(violation.into()),
+        None => Ok(()),
+    }
+}
+
+/// All violations of the proposal validation rules that were found by
+/// [`MlsGroup::validate_proposals()`], in the order in which they were found.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ProposalValidationReport {
+    violations: Vec<ProposalValidationError>,
+}
+
+impl ProposalValidationReport {
+    /// Returns `true` if no rule was violated.
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// Returns all violations that were found.
+    pub fn violations(&self) -> &[ProposalValidationError] {
+        &self.violations
     }
 }
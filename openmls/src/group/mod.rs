@@ -17,7 +17,9 @@ use crate::ciphersuite::*;
 use crate::extensions::*;
 use crate::utils::*;
 
+use openmls_traits::crypto::OpenMlsCrypto;
 use openmls_traits::random::OpenMlsRand;
+use openmls_traits::types::HashType;
 use openmls_traits::OpenMlsCryptoProvider;
 pub(crate) use serde::{Deserialize, Serialize};
 
@@ -29,6 +31,7 @@ pub use group_context::*;
 pub use managed_group::*;
 pub use mls_group::*;
 
+use std::sync::Arc;
 use tls_codec::TlsVecU32;
 use tls_codec::{TlsByteVecU8, TlsDeserialize, TlsSerialize, TlsSize};
 
@@ -45,6 +48,18 @@ impl GroupId {
             value: rng.rand().random_vec(16).unwrap().into(),
         }
     }
+    /// Derive a group ID deterministically from the given `seed`, e.g. the
+    /// name of a room. The seed is hashed with SHA-256, so the same seed
+    /// always yields the same 32 byte group ID.
+    pub fn from_seed(seed: &[u8], backend: &impl OpenMlsCryptoProvider) -> Self {
+        Self {
+            value: backend
+                .crypto()
+                .hash(HashType::Sha2_256, seed)
+                .unwrap()
+                .into(),
+        }
+    }
     pub fn from_slice(bytes: &[u8]) -> Self {
         GroupId {
             value: bytes.into(),
@@ -87,14 +102,56 @@ impl GroupContext {
     }
 }
 
+/// Callback that is invoked with the new group state whenever an [`MlsGroup`]
+/// enters a new epoch.
+pub type EpochChangeCallback = Arc<dyn Fn(&MlsGroup) + Send + Sync>;
+
 /// Configuration for an MLS group.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone)]
 pub struct MlsGroupConfig {
     /// Flag whether to send the ratchet tree along with the `GroupInfo` or not.
     /// Defaults to false.
     pub add_ratchet_tree_extension: bool,
     pub padding_block_size: u32,
+    /// Number of past epochs for which the application secrets are retained
+    /// to decrypt late messages. Defaults to 0.
     pub additional_as_epochs: u32,
+    /// How to handle committed proposals of a type that isn't supported.
+    /// Defaults to [`UnknownProposalPolicy::Reject`].
+    pub unknown_proposal_policy: UnknownProposalPolicy,
+    /// Called at the end of [`MlsGroup::merge_commit()`] and
+    /// [`MlsGroup::new_from_welcome_with_config()`] with the new group state,
+    /// e.g. to persist it. Defaults to `None`.
+    pub on_epoch_change: Option<EpochChangeCallback>,
+    /// Flag whether a `Welcome` for this group has to carry the ratchet tree
+    /// extension. If set, joining with
+    /// [`MlsGroup::new_from_welcome_with_config()`] without the extension and
+    /// without a ratchet tree fails with
+    /// [`WelcomeError::RatchetTreeRequiredButMissing`], so that the client can
+    /// fetch the tree and retry. Defaults to false.
+    pub require_tree_in_welcome: bool,
+    /// The maximum depth of a ratchet tree received in a `Welcome` or passed
+    /// to [`MlsGroup::new_from_welcome_with_config()`]. Deeper trees are
+    /// rejected with [`TreeError::TreeTooDeep`](crate::tree::TreeError::TreeTooDeep)
+    /// before they are processed. Defaults to 20, i.e. up to 2^20 members.
+    pub max_tree_depth: u32,
+}
+
+impl std::fmt::Debug for MlsGroupConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MlsGroupConfig")
+            .field(
+                "add_ratchet_tree_extension",
+                &self.add_ratchet_tree_extension,
+            )
+            .field("padding_block_size", &self.padding_block_size)
+            .field("additional_as_epochs", &self.additional_as_epochs)
+            .field("unknown_proposal_policy", &self.unknown_proposal_policy)
+            .field("on_epoch_change", &self.on_epoch_change.is_some())
+            .field("require_tree_in_welcome", &self.require_tree_in_welcome)
+            .field("max_tree_depth", &self.max_tree_depth)
+            .finish()
+    }
 }
 
 impl MlsGroupConfig {
@@ -110,10 +167,30 @@ impl Default for MlsGroupConfig {
             add_ratchet_tree_extension: false,
             padding_block_size: 10,
             additional_as_epochs: 0,
+            unknown_proposal_policy: UnknownProposalPolicy::default(),
+            on_epoch_change: None,
+            require_tree_in_welcome: false,
+            max_tree_depth: crate::tree::DEFAULT_MAX_TREE_DEPTH,
         }
     }
 }
 
+/// Policy for committed proposals whose type isn't supported, see
+/// [`ProposalType::is_supported()`](crate::messages::proposals::ProposalType::is_supported()).
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum UnknownProposalPolicy {
+    /// Reject the whole commit.
+    Reject,
+    /// Skip the proposal and apply the rest of the commit.
+    Ignore,
+}
+
+impl Default for UnknownProposalPolicy {
+    fn default() -> Self {
+        UnknownProposalPolicy::Reject
+    }
+}
+
 #[derive(
     PartialEq, Clone, Copy, Debug, Serialize, Deserialize, TlsDeserialize, TlsSerialize, TlsSize,
 )]
@@ -0,0 +1,269 @@
+//! # Known Answer Tests for message protection
+//!
+//! See https://github.com/mlswg/mls-implementations/blob/master/test-vectors.md
+//! for more description on the test vectors.
+//!
+//! ## Format:
+//!
+//! ```text
+//! {
+//!   "cipher_suite": /* uint16 */,
+//!   "n_leaves": /* uint32 */,
+//!   "encryption_secret": /* hex-encoded binary data */,
+//!   "sender_data_secret": /* hex-encoded binary data */,
+//!   "plaintext": /* hex-encoded binary data */,
+//!   "ciphertext": /* hex-encoded binary data */,
+//! }
+//! ```
+//!
+//! ## Verification:
+//!
+//! * `ciphertext` is an `MlsCiphertext` that successfully decrypts to an
+//!   `MlsPlaintext` encoded as `plaintext`, using the secret tree of
+//!   `n_leaves` leaves derived from `encryption_secret` and the sender data
+//!   secret `sender_data_secret`.
+
+#[cfg(test)]
+use crate::test_utils::{read, write};
+
+use crate::{
+    ciphersuite::{Ciphersuite, CiphersuiteName, Secret},
+    config::{Config, ProtocolVersion},
+    credentials::{CredentialBundle, CredentialType},
+    framing::*,
+    group::{GroupId, MlsGroup},
+    key_packages::KeyPackageBundle,
+    schedule::{EncryptionSecret, MembershipKey, SenderDataSecret},
+    test_utils::{bytes_to_hex, hex_to_bytes},
+    tree::{index::LeafIndex, secret_tree::SecretTree},
+};
+
+use openmls_rust_crypto::OpenMlsRustCrypto;
+use openmls_traits::{random::OpenMlsRand, types::SignatureScheme, OpenMlsCryptoProvider};
+use serde::{self, Deserialize, Serialize};
+use std::convert::TryFrom;
+use tls_codec::{Deserialize as TlsDeserialize, Serialize as TlsSerializeTrait};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct MessageProtectionTestVector {
+    pub cipher_suite: u16,
+    pub n_leaves: u32,
+    encryption_secret: String,
+    sender_data_secret: String,
+    plaintext: String,  // TLS serialized MlsPlaintext(application data)
+    ciphertext: String, // TLS serialized MlsCiphertext
+}
+
+/// Create a group with a single member that is only used for its epoch
+/// secrets and group context.
+fn group(
+    ciphersuite: &'static Ciphersuite,
+    backend: &impl OpenMlsCryptoProvider,
+) -> (MlsGroup, CredentialBundle) {
+    let credential_bundle = CredentialBundle::new(
+        b"client".to_vec(),
+        CredentialType::Basic,
+        SignatureScheme::from(ciphersuite.name()),
+        backend,
+    )
+    .unwrap();
+    let key_package_bundle = KeyPackageBundle::new(
+        &[ciphersuite.name()],
+        &credential_bundle,
+        backend,
+        Vec::new(),
+    )
+    .unwrap();
+    (
+        MlsGroup::builder(GroupId::random(backend), key_package_bundle)
+            .build(backend)
+            .expect("Error creating MlsGroup"),
+        credential_bundle,
+    )
+}
+
+pub fn generate_test_vector(
+    n_leaves: u32,
+    ciphersuite: &'static Ciphersuite,
+) -> MessageProtectionTestVector {
+    let crypto = OpenMlsRustCrypto::default();
+
+    // Generate random secrets.
+    let encryption_secret_bytes = crypto.rand().random_vec(ciphersuite.hash_length()).unwrap();
+    let sender_data_secret_bytes = crypto.rand().random_vec(ciphersuite.hash_length()).unwrap();
+
+    let (mut group, credential_bundle) = group(ciphersuite, &crypto);
+    *group.epoch_secrets_mut().sender_data_secret_mut() = SenderDataSecret::from_slice(
+        &sender_data_secret_bytes,
+        ProtocolVersion::default(),
+        ciphersuite,
+    );
+    *group.secret_tree_mut() = SecretTree::new(
+        EncryptionSecret::from_slice(
+            &encryption_secret_bytes,
+            ProtocolVersion::default(),
+            ciphersuite,
+        ),
+        LeafIndex::from(n_leaves),
+    );
+
+    // Build and encrypt an application message from the last leaf.
+    let sender = LeafIndex::from(n_leaves - 1);
+    let membership_key = MembershipKey::from_secret(Secret::random(
+        ciphersuite,
+        &crypto,
+        None, /* MLS version */
+    ));
+    let aad = crypto.rand().random_vec(48).unwrap();
+    let message = crypto.rand().random_vec(64).unwrap();
+    let mut plaintext = MlsPlaintext::new_application(
+        sender,
+        &aad,
+        &message,
+        &credential_bundle,
+        group.context(),
+        &membership_key,
+        &crypto,
+    )
+    .unwrap();
+    plaintext.remove_membership_tag();
+    let ciphertext = MlsCiphertext::try_from_plaintext(
+        &plaintext,
+        ciphersuite,
+        &crypto,
+        group.context(),
+        sender,
+        Secrets {
+            epoch_secrets: group.epoch_secrets(),
+            secret_tree: &mut group.secret_tree_mut(),
+        },
+        0,
+    )
+    .expect("Could not create MlsCiphertext");
+
+    MessageProtectionTestVector {
+        cipher_suite: ciphersuite.name() as u16,
+        n_leaves,
+        encryption_secret: bytes_to_hex(&encryption_secret_bytes),
+        sender_data_secret: bytes_to_hex(&sender_data_secret_bytes),
+        plaintext: bytes_to_hex(
+            &plaintext
+                .tls_serialize_detached()
+                .expect("Error encoding plaintext"),
+        ),
+        ciphertext: bytes_to_hex(
+            &ciphertext
+                .tls_serialize_detached()
+                .expect("Error encoding ciphertext"),
+        ),
+    }
+}
+
+#[test]
+fn write_test_vectors() {
+    let mut tests = Vec::new();
+    const NUM_LEAVES: u32 = 10;
+
+    for ciphersuite in Config::supported_ciphersuites() {
+        for n_leaves in 1..NUM_LEAVES {
+            let test = generate_test_vector(n_leaves, ciphersuite);
+            tests.push(test);
+        }
+    }
+
+    write("test_vectors/kat_message_protection-new.json", &tests);
+}
+
+pub fn run_test_vector(
+    test_vector: MessageProtectionTestVector,
+) -> Result<(), MessageProtectionTestVectorError> {
+    let ciphersuite =
+        CiphersuiteName::try_from(test_vector.cipher_suite).expect("Invalid ciphersuite");
+    let crypto = OpenMlsRustCrypto::default();
+    let ciphersuite = match Config::ciphersuite(ciphersuite) {
+        Ok(cs) => cs,
+        Err(_) => {
+            log::info!(
+                "Unsupported ciphersuite {} in test vector. Skipping ...",
+                ciphersuite
+            );
+            return Ok(());
+        }
+    };
+    log::debug!("Testing test vector for ciphersuite {:?}", ciphersuite);
+    log::trace!("  {:?}", test_vector);
+
+    // Read input values.
+    let mut secret_tree = SecretTree::new(
+        EncryptionSecret::from_slice(
+            &hex_to_bytes(&test_vector.encryption_secret),
+            ProtocolVersion::default(),
+            ciphersuite,
+        ),
+        LeafIndex::from(test_vector.n_leaves),
+    );
+    let (mut group, _credential_bundle) = group(ciphersuite, &crypto);
+    *group.epoch_secrets_mut().sender_data_secret_mut() = SenderDataSecret::from_slice(
+        &hex_to_bytes(&test_vector.sender_data_secret),
+        ProtocolVersion::default(),
+        ciphersuite,
+    );
+    let ciphertext_bytes = hex_to_bytes(&test_vector.ciphertext);
+    let ciphertext = MlsCiphertext::tls_deserialize(&mut ciphertext_bytes.as_slice())
+        .expect("Error decoding ciphertext");
+
+    // Decrypt the message and compare it to the expected plaintext.
+    // Note that we can't get an MlsPlaintext without the signer's credential.
+    // We encode the VerifiableMlsPlaintext instead.
+    let plaintext = match ciphertext.to_plaintext(
+        ciphersuite,
+        &crypto,
+        group.epoch_secrets(),
+        &mut secret_tree,
+    ) {
+        Ok(plaintext) => plaintext,
+        Err(e) => {
+            log::error!("  Decryption failed: {:?}", e);
+            if cfg!(test) {
+                panic!("Decryption failed");
+            }
+            return Err(MessageProtectionTestVectorError::DecryptionFailed);
+        }
+    };
+    let plaintext_bytes = plaintext
+        .tls_serialize_detached()
+        .expect("Error encoding plaintext");
+    if plaintext_bytes != hex_to_bytes(&test_vector.plaintext) {
+        log::error!("  Plaintext mismatch");
+        log::debug!("    Got:      {:x?}", plaintext_bytes);
+        log::debug!("    Expected: {:x?}", hex_to_bytes(&test_vector.plaintext));
+        if cfg!(test) {
+            panic!("Plaintext mismatch");
+        }
+        return Err(MessageProtectionTestVectorError::PlaintextMismatch);
+    }
+    log::debug!("  Finished message protection test vector verification");
+    Ok(())
+}
+
+#[test]
+fn read_test_vectors() {
+    let tests: Vec<MessageProtectionTestVector> = read("test_vectors/kat_message_protection.json");
+
+    for test_vector in tests {
+        match run_test_vector(test_vector) {
+            Ok(_) => {}
+            Err(e) => panic!(
+                "Error while checking message protection test vector.\n{:?}",
+                e
+            ),
+        }
+    }
+}
+
+implement_error! {
+    pub enum MessageProtectionTestVectorError {
+        DecryptionFailed = "The ciphertext in the test vector could not be decrypted.",
+        PlaintextMismatch = "The decrypted plaintext doesn't match the one in the test vector.",
+    }
+}
@@ -1,6 +1,9 @@
 //! Unit tests for the MLS group
 
+pub mod kat_message_protection;
 pub mod kat_messages;
 pub mod kat_transcripts;
 #[cfg(test)]
+mod test_group_id;
+#[cfg(test)]
 mod test_validation;
@@ -0,0 +1,27 @@
+//! Unit tests for group IDs
+
+use openmls_rust_crypto::OpenMlsRustCrypto;
+use tls_codec::Serialize;
+
+use crate::group::GroupId;
+
+#[test]
+fn group_id_from_seed() {
+    let crypto = OpenMlsRustCrypto::default();
+
+    // The same seed always yields the same group ID.
+    let group_id = GroupId::from_seed(b"#general", &crypto);
+    assert_eq!(group_id, GroupId::from_seed(b"#general", &crypto));
+    assert_ne!(group_id, GroupId::from_seed(b"#random", &crypto));
+
+    // The group ID fits into its opaque<0..255> wire encoding.
+    assert!(!group_id.as_slice().is_empty());
+    assert!(group_id.as_slice().len() <= u8::MAX as usize);
+    assert_eq!(
+        group_id
+            .tls_serialize_detached()
+            .expect("Error encoding group ID.")
+            .len(),
+        1 + group_id.as_slice().len()
+    );
+}
@@ -1,5 +1,6 @@
 use log::error;
 use openmls_traits::crypto::OpenMlsCrypto;
+use openmls_traits::key_store::OpenMlsKeyStore;
 use openmls_traits::types::HpkeKeyPair;
 use openmls_traits::types::SignatureScheme;
 use openmls_traits::OpenMlsCryptoProvider;
@@ -16,6 +17,9 @@ use crate::extensions::{
     CapabilitiesExtension, Extension, ExtensionError, ExtensionType, LifetimeExtension,
     ParentHashExtension,
 };
+use crate::key_store::ConsumedKeyPackage;
+
+use std::collections::HashSet;
 
 use serde::{
     de::{self, MapAccess, SeqAccess, Visitor},
@@ -192,6 +196,21 @@ impl KeyPackage {
         })
     }
 
+    /// Get the encoded payload of this key package, i.e. the bytes that are
+    /// covered by its signature. This allows the signature to be computed by
+    /// an external signer.
+    pub fn unsigned_payload(&self) -> Result<Vec<u8>, tls_codec::Error> {
+        Ok(self.encoded.clone())
+    }
+
+    /// Assemble a key package from a `payload` and a `signature` over its
+    /// encoding that was computed by an external signer.
+    /// Note that the signature is not checked here. Call [`KeyPackage::verify()`]
+    /// on the result before using it.
+    pub fn with_signature(payload: KeyPackagePayload, signature: Signature) -> Self {
+        Self::from_payload(payload, signature)
+    }
+
     /// Compute the hash of the encoding of this key package.
     pub fn hash(&self, backend: &impl OpenMlsCryptoProvider) -> Vec<u8> {
         // FIXME: remove unwrap
@@ -296,6 +315,29 @@ impl KeyPackage {
         None
     }
 
+    /// Check that no two extensions of this key package have the same type.
+    /// Returns `KeyPackageError::DuplicateExtension` otherwise.
+    pub(crate) fn check_extension_uniqueness(&self) -> Result<(), KeyPackageError> {
+        let mut extension_types = HashSet::new();
+        if self
+            .payload
+            .extensions
+            .iter()
+            .all(|e| extension_types.insert(e.extension_type()))
+        {
+            Ok(())
+        } else {
+            Err(KeyPackageError::DuplicateExtension)
+        }
+    }
+
+    /// Replace the extensions of this key package without re-signing it.
+    #[cfg(test)]
+    pub(crate) fn set_extensions(&mut self, extensions: Vec<Extension>) {
+        self.payload.extensions = extensions.into();
+        self.encoded = self.payload.unsigned_payload().unwrap();
+    }
+
     /// Get a reference to the HPKE init key.
     pub(crate) fn hpke_init_key(&self) -> &HpkePublicKey {
         &self.payload.hpke_init_key
@@ -572,6 +614,19 @@ impl KeyPackageBundle {
     pub fn unsigned(self) -> KeyPackageBundlePayload {
         self.into()
     }
+
+    /// Returns `true` if the private key of this bundle was already used to
+    /// decrypt a Welcome message, i.e. if it is recorded as consumed in the
+    /// key store of the `backend`. A consumed bundle can't be used to join
+    /// another group and should be deleted from the key store.
+    pub fn is_consumed(&self, backend: &impl OpenMlsCryptoProvider) -> bool {
+        backend
+            .key_store()
+            .read::<_, ConsumedKeyPackage>(&ConsumedKeyPackage::key(
+                &self.key_package.hash(backend),
+            ))
+            .is_some()
+    }
 }
 
 /// Private `KeyPackageBundle` functions.
@@ -159,3 +159,48 @@ fn test_mismatch() {
         KeyPackageBundle::new(&[ciphersuite_name], &credential_bundle, &crypto, vec![]).is_ok()
     );
 }
+
+#[test]
+fn external_signature() {
+    let crypto = OpenMlsRustCrypto::default();
+
+    for ciphersuite in Config::supported_ciphersuites() {
+        let credential_bundle = CredentialBundle::new(
+            vec![1, 2, 3],
+            CredentialType::Basic,
+            ciphersuite.name().into(),
+            &crypto,
+        )
+        .unwrap();
+        let kpb = KeyPackageBundle::new(&[ciphersuite.name()], &credential_bundle, &crypto, vec![])
+            .unwrap();
+
+        // The bytes to sign are the encoded payload.
+        let key_package = kpb.key_package().clone();
+        let unsigned_payload = key_package.unsigned_payload().unwrap();
+        let payload: KeyPackagePayload = key_package.into();
+        assert_eq!(
+            Signable::unsigned_payload(&payload).unwrap(),
+            unsigned_payload
+        );
+
+        // Sign the payload "out of process" and only hand back the raw
+        // signature bytes.
+        let raw_signature = credential_bundle
+            .sign(&crypto, &unsigned_payload)
+            .unwrap()
+            .as_slice()
+            .to_vec();
+        let key_package = KeyPackage::with_signature(payload.clone(), raw_signature.into());
+        assert!(key_package.verify(&crypto).is_ok());
+        assert_eq!(&key_package, kpb.key_package());
+
+        // A signature over different bytes must not verify.
+        let wrong_signature = credential_bundle.sign(&crypto, b"not the payload").unwrap();
+        let key_package = KeyPackage::with_signature(payload, wrong_signature);
+        assert_eq!(
+            key_package.verify(&crypto),
+            Err(KeyPackageError::InvalidSignature)
+        );
+    }
+}
@@ -33,3 +33,29 @@ impl ToKeyStoreValue for CredentialBundle {
         serde_json::to_vec(self).map_err(|_| "Error serializing key package bundle.")
     }
 }
+
+/// Marker recording that the private key of a key package was used to decrypt
+/// a Welcome message. The marker is stored in the key store under
+/// [`ConsumedKeyPackage::key()`] of the key package's hash.
+pub(crate) struct ConsumedKeyPackage;
+
+impl ConsumedKeyPackage {
+    /// The key store ID of the marker for the key package with the given hash.
+    pub(crate) fn key(key_package_hash: &[u8]) -> (&'static str, Vec<u8>) {
+        ("consumed key package", key_package_hash.to_vec())
+    }
+}
+
+impl FromKeyStoreValue for ConsumedKeyPackage {
+    type Error = &'static str;
+    fn from_key_store_value(_ksv: &[u8]) -> Result<Self, Self::Error> {
+        Ok(Self)
+    }
+}
+
+impl ToKeyStoreValue for ConsumedKeyPackage {
+    type Error = &'static str;
+    fn to_key_store_value(&self) -> Result<Vec<u8>, Self::Error> {
+        Ok(vec![])
+    }
+}
@@ -0,0 +1,106 @@
+//! This module contains the `ExternalJoinPackage`, which bundles everything a
+//! new member needs to join a group through an external commit. A group member
+//! creates it with
+//! [`MlsGroup::export_external_join_package()`](crate::group::MlsGroup::export_external_join_package())
+//! and publishes it, e.g. in a directory.
+//!
+//! ```text
+//! struct {
+//!     uint8 version = 1;
+//!     PublicGroupState public_group_state;
+//!     optional<Node> ratchet_tree<1..2^32-1>;
+//! } ExternalJoinPackage;
+//! ```
+
+use std::io::{Read, Write};
+
+use tls_codec::{Deserialize, Serialize, Size, TlsVecU32};
+
+use super::public_group_state::VerifiablePublicGroupState;
+use crate::tree::node::Node;
+
+/// The version of the `ExternalJoinPackage` encoding.
+pub(crate) const EXTERNAL_JOIN_PACKAGE_VERSION: u8 = 1;
+
+/// A signed `PublicGroupState` together with the ratchet tree of the group.
+///
+/// The `PublicGroupState` has to be verified by the joiner before it is used,
+/// see [`VerifiablePublicGroupState`].
+#[derive(Debug, Clone)]
+pub struct ExternalJoinPackage {
+    public_group_state: VerifiablePublicGroupState,
+    ratchet_tree: TlsVecU32<Option<Node>>,
+}
+
+impl ExternalJoinPackage {
+    /// Create a new `ExternalJoinPackage` from the `public_group_state` and
+    /// the `ratchet_tree` of a group.
+    pub(crate) fn new(
+        public_group_state: VerifiablePublicGroupState,
+        ratchet_tree: Vec<Option<Node>>,
+    ) -> Self {
+        Self {
+            public_group_state,
+            ratchet_tree: ratchet_tree.into(),
+        }
+    }
+
+    /// Get the version of the encoding.
+    pub fn version(&self) -> u8 {
+        EXTERNAL_JOIN_PACKAGE_VERSION
+    }
+
+    /// Get the unverified `PublicGroupState`.
+    pub fn public_group_state(&self) -> &VerifiablePublicGroupState {
+        &self.public_group_state
+    }
+
+    /// Get the ratchet tree of the group.
+    pub fn ratchet_tree(&self) -> &[Option<Node>] {
+        self.ratchet_tree.as_slice()
+    }
+
+    /// Split the package into the unverified `PublicGroupState` and the
+    /// ratchet tree.
+    pub fn into_parts(self) -> (VerifiablePublicGroupState, Vec<Option<Node>>) {
+        (
+            self.public_group_state,
+            self.ratchet_tree.as_slice().to_vec(),
+        )
+    }
+}
+
+impl Size for ExternalJoinPackage {
+    fn tls_serialized_len(&self) -> usize {
+        EXTERNAL_JOIN_PACKAGE_VERSION.tls_serialized_len()
+            + self.public_group_state.tls_serialized_len()
+            + self.ratchet_tree.tls_serialized_len()
+    }
+}
+
+impl Serialize for ExternalJoinPackage {
+    fn tls_serialize<W: Write>(&self, writer: &mut W) -> Result<usize, tls_codec::Error> {
+        let written = EXTERNAL_JOIN_PACKAGE_VERSION.tls_serialize(writer)?;
+        let written = written + self.public_group_state.tls_serialize(writer)?;
+        self.ratchet_tree.tls_serialize(writer).map(|l| l + written)
+    }
+}
+
+// Deserialize manually in order to reject unknown versions.
+impl Deserialize for ExternalJoinPackage {
+    fn tls_deserialize<R: Read>(bytes: &mut R) -> Result<Self, tls_codec::Error> {
+        let version = u8::tls_deserialize(bytes)?;
+        if version != EXTERNAL_JOIN_PACKAGE_VERSION {
+            return Err(tls_codec::Error::DecodingError(format!(
+                "Unsupported ExternalJoinPackage version {}",
+                version
+            )));
+        }
+        let public_group_state = VerifiablePublicGroupState::tls_deserialize(bytes)?;
+        let ratchet_tree = TlsVecU32::tls_deserialize(bytes)?;
+        Ok(Self {
+            public_group_state,
+            ratchet_tree,
+        })
+    }
+}
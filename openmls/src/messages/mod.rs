@@ -2,13 +2,12 @@ use crate::ciphersuite::{signable::*, *};
 use crate::config::ProtocolVersion;
 use crate::extensions::*;
 use crate::group::*;
+use crate::key_packages::KeyPackageBundle;
 use crate::schedule::psk::PreSharedKeys;
 use crate::schedule::JoinerSecret;
 use crate::tree::{index::*, *};
 
 use openmls_traits::types::HpkeCiphertext;
-
-#[cfg(any(feature = "test-utils", test))]
 use openmls_traits::OpenMlsCryptoProvider;
 
 use serde::{Deserialize, Serialize};
@@ -16,6 +15,7 @@ use serde::{Deserialize, Serialize};
 mod codec;
 
 pub mod errors;
+pub(crate) mod external_join_package;
 pub(crate) mod proposals;
 pub(crate) mod public_group_state;
 
@@ -112,6 +112,23 @@ impl Welcome {
         &self.version
     }
 
+    /// Decrypt the GroupInfo with the `key_package_bundle` of the new member
+    /// and return its extensions and metadata, e.g. to decide whether to join
+    /// the group. The group isn't joined and the key package is not marked as
+    /// consumed.
+    ///
+    /// Note that neither the ratchet tree nor the signature on the GroupInfo
+    /// are verified, so the returned values are only authenticated once the
+    /// group is joined with [`MlsGroup::new_from_welcome()`].
+    pub fn inspect_group_info(
+        &self,
+        key_package_bundle: &KeyPackageBundle,
+        psk_fetcher_option: Option<PskFetcher>,
+        backend: &impl OpenMlsCryptoProvider,
+    ) -> Result<GroupInfoView, WelcomeError> {
+        MlsGroup::inspect_welcome(self, key_package_bundle, psk_fetcher_option, backend)
+    }
+
     /// Set the welcome's encrypted group info.
     #[cfg(test)]
     pub fn set_encrypted_group_info(&mut self, encrypted_group_info: Vec<u8>) {
@@ -133,6 +150,18 @@ impl Commit {
         self.path.is_some()
     }
 
+    /// Returns `true` if the commit is an external commit, i.e. if it contains
+    /// an ExternalInit proposal. The committer of an external commit is not a
+    /// member of the group yet. `false` otherwise.
+    pub fn is_external(&self) -> bool {
+        self.proposals.iter().any(|proposal_or_ref| {
+            matches!(
+                proposal_or_ref,
+                ProposalOrRef::Proposal(Proposal::ExternalInit(_))
+            )
+        })
+    }
+
     #[cfg(any(feature = "test-utils", test))]
     pub fn path(&self) -> &Option<UpdatePath> {
         &self.path
@@ -263,6 +292,30 @@ impl GroupInfo {
         self.payload.other_extensions = extensions.into();
     }
 
+    /// Set the tree hash of the group info.
+    #[cfg(test)]
+    pub(crate) fn set_tree_hash(&mut self, tree_hash: Vec<u8>) {
+        self.payload.tree_hash = tree_hash.into();
+    }
+
+    /// Set the confirmed transcript hash of the group info.
+    #[cfg(test)]
+    pub(crate) fn set_confirmed_transcript_hash(&mut self, confirmed_transcript_hash: Vec<u8>) {
+        self.payload.confirmed_transcript_hash = confirmed_transcript_hash.into();
+    }
+
+    /// Set the signer index of the group info.
+    #[cfg(test)]
+    pub(crate) fn set_signer_index(&mut self, signer_index: LeafIndex) {
+        self.payload.signer_index = signer_index;
+    }
+
+    /// Replace the signature of the group info.
+    #[cfg(test)]
+    pub(crate) fn set_signature(&mut self, signature: Signature) {
+        self.signature = signature;
+    }
+
     /// Re-sign the group info.
     #[cfg(test)]
     pub(crate) fn re_sign(
@@ -290,6 +343,66 @@ impl SignedStruct<GroupInfoPayload> for GroupInfo {
     }
 }
 
+/// The unverified content of a decrypted GroupInfo, see
+/// [`Welcome::inspect_group_info()`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupInfoView {
+    group_id: GroupId,
+    epoch: GroupEpoch,
+    group_context_extensions: Vec<Extension>,
+    other_extensions: Vec<Extension>,
+    signer_index: LeafIndex,
+}
+
+impl GroupInfoView {
+    /// Get the group ID.
+    pub fn group_id(&self) -> &GroupId {
+        &self.group_id
+    }
+
+    /// Get the epoch.
+    pub fn epoch(&self) -> GroupEpoch {
+        self.epoch
+    }
+
+    /// Get the extensions of the group context.
+    pub fn group_context_extensions(&self) -> &[Extension] {
+        &self.group_context_extensions
+    }
+
+    /// Get the other extensions of the GroupInfo, e.g. the ratchet tree
+    /// extension.
+    pub fn other_extensions(&self) -> &[Extension] {
+        &self.other_extensions
+    }
+
+    /// Get the required capabilities extension of the group context, if the
+    /// group has one.
+    pub fn required_capabilities(&self) -> Option<&RequiredCapabilitiesExtension> {
+        self.group_context_extensions
+            .iter()
+            .find_map(|extension| extension.as_required_capabilities_extension().ok())
+    }
+
+    /// Get the leaf index of the member that signed the GroupInfo.
+    pub fn signer_index(&self) -> LeafIndex {
+        self.signer_index
+    }
+}
+
+impl From<GroupInfo> for GroupInfoView {
+    fn from(group_info: GroupInfo) -> Self {
+        let payload = group_info.payload;
+        Self {
+            group_id: payload.group_id,
+            epoch: payload.epoch,
+            group_context_extensions: payload.group_context_extensions.as_slice().to_vec(),
+            other_extensions: payload.other_extensions.as_slice().to_vec(),
+            signer_index: payload.signer_index,
+        }
+    }
+}
+
 /// PathSecret
 ///
 /// > 11.2.2. Welcoming New Members
@@ -352,16 +465,27 @@ impl GroupSecrets {
     }
 
     /// Set the config for the secrets, i.e. cipher suite and MLS version.
+    ///
+    /// Returns a `WelcomeError::MalformedGroupSecrets` if the joiner secret or
+    /// the path secret don't have the length of the ciphersuite's hash.
     pub(crate) fn config(
         mut self,
         ciphersuite: &'static Ciphersuite,
         mls_version: ProtocolVersion,
-    ) -> GroupSecrets {
+    ) -> Result<GroupSecrets, WelcomeError> {
+        if self.joiner_secret.as_slice().len() != ciphersuite.hash_length() {
+            return Err(WelcomeError::MalformedGroupSecrets);
+        }
+        if let Some(s) = &self.path_secret {
+            if s.path_secret.as_slice().len() != ciphersuite.hash_length() {
+                return Err(WelcomeError::MalformedGroupSecrets);
+            }
+        }
         self.joiner_secret.config(ciphersuite, mls_version);
         if let Some(s) = &mut self.path_secret {
             s.path_secret.config(ciphersuite, mls_version);
         }
-        self
+        Ok(self)
     }
 
     #[cfg(any(feature = "test-utils", test))]
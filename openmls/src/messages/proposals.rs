@@ -277,6 +277,15 @@ pub struct ExternalInitProposal {
     kem_output: TlsByteVecU16,
 }
 
+#[cfg(test)]
+impl From<Vec<u8>> for ExternalInitProposal {
+    fn from(kem_output: Vec<u8>) -> Self {
+        Self {
+            kem_output: kem_output.into(),
+        }
+    }
+}
+
 /// TODO: This is going away in https://github.com/mlswg/mls-protocol/pull/510
 /// ```text
 /// struct {
@@ -109,6 +109,33 @@ impl SignedStruct<PublicGroupStateTbs> for PublicGroupState {
     }
 }
 
+impl VerifiablePublicGroupState {
+    /// Get the index of the member that signed the `PublicGroupState`. This
+    /// is needed to look up the credential for verifying it.
+    pub fn signer_index(&self) -> LeafIndex {
+        self.tbs.signer_index
+    }
+}
+
+impl From<PublicGroupState> for VerifiablePublicGroupState {
+    fn from(pgs: PublicGroupState) -> Self {
+        Self {
+            tbs: PublicGroupStateTbs {
+                ciphersuite: pgs.ciphersuite,
+                group_id: pgs.group_id,
+                epoch: pgs.epoch,
+                tree_hash: pgs.tree_hash,
+                interim_transcript_hash: pgs.interim_transcript_hash,
+                group_context_extensions: pgs.group_context_extensions,
+                other_extensions: pgs.other_extensions,
+                external_pub: pgs.external_pub,
+                signer_index: pgs.signer_index,
+            },
+            signature: pgs.signature,
+        }
+    }
+}
+
 impl<'a> Verifiable for VerifiablePublicGroupState {
     fn unsigned_payload(&self) -> Result<Vec<u8>, tls_codec::Error> {
         self.tbs.tls_serialize_detached()
@@ -12,10 +12,13 @@ use crate::{
     },
     key_packages::KeyPackageBundle,
     messages::{
+        external_join_package::ExternalJoinPackage,
         public_group_state::{PublicGroupState, VerifiablePublicGroupState},
         LeafIndex, MlsGroup,
     },
     prelude::FramingParameters,
+    test_utils::setup_group_with_members,
+    tree::index::NodeIndex,
 };
 
 /// Tests the creation of a `PublicGroupState` and verifies it was correctly
@@ -120,3 +123,71 @@ fn test_pgs() {
         assert_eq!(pgs, pgs_decoded)
     }
 }
+
+/// Tests that an `ExternalJoinPackage` survives an encoding round trip and
+/// that the `PublicGroupState` in it can be verified with the signer's
+/// credential from the ratchet tree in the package.
+#[test]
+fn test_external_join_package() {
+    let crypto = OpenMlsRustCrypto::default();
+    for ciphersuite in Config::supported_ciphersuites() {
+        let (_creator_group, members) =
+            setup_group_with_members(ciphersuite, &["Bob", "Charlie"], &crypto);
+        let (bob_credential_bundle, bob_group) = &members[0];
+
+        let package = bob_group
+            .export_external_join_package(bob_credential_bundle, &crypto)
+            .expect("Could not export the external join package");
+
+        let encoded = package.tls_serialize_detached().expect("Could not encode");
+        let decoded = ExternalJoinPackage::tls_deserialize(&mut encoded.as_slice())
+            .expect("Could not decode");
+
+        assert_eq!(decoded.version(), 1);
+        assert_eq!(decoded.ratchet_tree(), package.ratchet_tree());
+        assert_eq!(
+            decoded.ratchet_tree(),
+            bob_group.tree().public_key_tree_copy().as_slice()
+        );
+
+        // Look up the signer's credential in the ratchet tree from the package.
+        let (verifiable_pgs, ratchet_tree) = decoded.into_parts();
+        let signer_index = NodeIndex::from(verifiable_pgs.signer_index());
+        let signer_credential = ratchet_tree[signer_index.as_usize()]
+            .as_ref()
+            .and_then(|node| node.key_package())
+            .expect("The signer is not in the ratchet tree")
+            .credential()
+            .clone();
+        assert_eq!(&signer_credential, bob_credential_bundle.credential());
+
+        let pgs: PublicGroupState = verifiable_pgs
+            .verify(&crypto, &signer_credential)
+            .expect("Could not verify the public group state");
+        let expected_pgs = bob_group
+            .export_public_group_state(&crypto, bob_credential_bundle)
+            .expect("Could not export the public group state");
+        assert_eq!(pgs.group_id, expected_pgs.group_id);
+        assert_eq!(pgs.epoch, expected_pgs.epoch);
+        assert_eq!(pgs.tree_hash, expected_pgs.tree_hash);
+        assert_eq!(pgs.signer_index, expected_pgs.signer_index);
+    }
+}
+
+/// Tests that an `ExternalJoinPackage` with an unknown version is rejected.
+#[test]
+fn test_external_join_package_version() {
+    let crypto = OpenMlsRustCrypto::default();
+    for ciphersuite in Config::supported_ciphersuites() {
+        let (_creator_group, members) = setup_group_with_members(ciphersuite, &["Bob"], &crypto);
+        let (bob_credential_bundle, bob_group) = &members[0];
+
+        let package = bob_group
+            .export_external_join_package(bob_credential_bundle, &crypto)
+            .expect("Could not export the external join package");
+        let mut encoded = package.tls_serialize_detached().expect("Could not encode");
+        encoded[0] = 2;
+
+        assert!(ExternalJoinPackage::tls_deserialize(&mut encoded.as_slice()).is_err());
+    }
+}
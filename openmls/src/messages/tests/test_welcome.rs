@@ -2,10 +2,13 @@
 
 use crate::{
     ciphersuite::{signable::Signable, AeadKey, AeadNonce, CiphersuiteName, Mac, Secret},
-    config::Config,
+    config::{Config, ProtocolVersion},
     credentials::{CredentialBundle, CredentialType},
-    group::{GroupEpoch, GroupId},
-    messages::{ConfirmationTag, EncryptedGroupSecrets, GroupInfoPayload, Welcome},
+    group::{GroupEpoch, GroupId, WelcomeError},
+    messages::{
+        ConfirmationTag, EncryptedGroupSecrets, GroupInfoPayload, GroupSecrets, PathSecret, Welcome,
+    },
+    schedule::{psk::PreSharedKeys, JoinerSecret},
     tree::index::LeafIndex,
 };
 
@@ -155,3 +158,37 @@ fn invalid_welcomes() {
     let msg = Welcome::tls_deserialize(&mut bytes);
     assert!(msg.is_err());
 }
+
+#[test]
+fn group_secrets_with_wrong_secret_lengths() {
+    let crypto = OpenMlsRustCrypto::default();
+    let version = ProtocolVersion::default();
+    for ciphersuite in Config::supported_ciphersuites() {
+        let psks = PreSharedKeys {
+            psks: Vec::new().into(),
+        };
+        let good_secret = vec![1u8; ciphersuite.hash_length()];
+        let short_secret = vec![1u8; ciphersuite.hash_length() - 1];
+        let decode_and_config = |joiner_secret: &[u8], path_secret: Option<&[u8]>| {
+            let joiner_secret =
+                JoinerSecret::from(Secret::from_slice(joiner_secret, version, ciphersuite));
+            let path_secret = path_secret
+                .map(|bytes| PathSecret::from(Secret::from_slice(bytes, version, ciphersuite)));
+            let encoded = GroupSecrets::new_encoded(&joiner_secret, path_secret.as_ref(), &psks)
+                .expect("Could not encode GroupSecrets");
+            GroupSecrets::tls_deserialize(&mut encoded.as_slice())
+                .expect("Could not decode GroupSecrets")
+                .config(ciphersuite, version)
+        };
+
+        assert!(decode_and_config(&good_secret, Some(&good_secret)).is_ok());
+        assert_eq!(
+            decode_and_config(&short_secret, None).err(),
+            Some(WelcomeError::MalformedGroupSecrets)
+        );
+        assert_eq!(
+            decode_and_config(&good_secret, Some(&short_secret)).err(),
+            Some(WelcomeError::MalformedGroupSecrets)
+        );
+    }
+}
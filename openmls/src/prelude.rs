@@ -4,7 +4,9 @@
 pub use crate::group::MlsGroup;
 pub use crate::group::MlsGroupConfig;
 pub use crate::group::{
+    policy::GroupPolicy,
     proposals::{ProposalStore, StagedProposal},
+    mls_group::validation::ProposalValidationReport,
     GroupEvent, InvalidMessageError, ManagedGroup, ManagedGroupCallbacks, ManagedGroupConfig,
     ManagedGroupError, Removal, UpdatePolicy, WireFormat,
 };
@@ -27,10 +29,11 @@ pub use crate::group::GroupId;
 pub use crate::key_packages::*;
 pub use crate::key_store::*;
 pub use crate::messages::{
+    external_join_package::ExternalJoinPackage,
     proposals::{
         AddProposal, PreSharedKeyProposal, ReInitProposal, RemoveProposal, UpdateProposal,
     },
-    Welcome,
+    GroupInfoView, Welcome,
 };
 pub use crate::schedule::psk::{
     BranchPsk, ExternalPsk, PreSharedKeyId, PreSharedKeys, Psk, PskType, ReinitPsk,
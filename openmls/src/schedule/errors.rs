@@ -46,3 +46,12 @@ implement_error! {
         ExternalPubMismatch = "The computed external public key doesn't match the one in the test vector.",
     }
 }
+
+#[cfg(any(feature = "test-utils", test))]
+implement_error! {
+    pub enum WelcomeTestVectorError {
+        WelcomeSecretMismatch = "The computed welcome secret doesn't match the one in the test vector.",
+        WelcomeKeyMismatch = "The computed welcome key doesn't match the one in the test vector.",
+        WelcomeNonceMismatch = "The computed welcome nonce doesn't match the one in the test vector.",
+    }
+}
@@ -0,0 +1,190 @@
+//! # Known Answer Tests for the welcome key and nonce
+//!
+//! See https://github.com/mlswg/mls-implementations/blob/master/test-vectors.md
+//! for more description on the test vectors.
+//!
+//! The welcome secret is derived from the joiner secret and the PSK secret.
+//! The AEAD key and nonce that protect the `GroupInfo` in a `Welcome` message
+//! are derived from the welcome secret.
+
+use std::convert::TryFrom;
+
+use crate::{
+    ciphersuite::{Ciphersuite, CiphersuiteName, Secret},
+    config::{Config, ProtocolVersion},
+    group::{GroupEpoch, GroupId},
+    prelude::{BranchPsk, Psk, PskType::Branch},
+    schedule::{JoinerSecret, KeySchedule},
+    test_utils::{bytes_to_hex, hex_to_bytes},
+};
+
+#[cfg(test)]
+use crate::test_utils::{read, write};
+
+use openmls_rust_crypto::OpenMlsRustCrypto;
+use openmls_traits::random::OpenMlsRand;
+use rand::{rngs::OsRng, RngCore};
+use serde::{self, Deserialize, Serialize};
+use tls_codec::{Deserialize as TlsDeserialize, Serialize as TlsSerializeTrait};
+
+use super::{errors::WelcomeTestVectorError, PreSharedKeyId, PskSecret};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+struct PskValue {
+    psk_id: String, /* hex encoded PreSharedKeyID */
+    psk: String,    /* hex-encoded binary data */
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct WelcomeTestVector {
+    pub cipher_suite: u16,
+    joiner_secret: String,
+    psks: Vec<PskValue>,
+
+    // Computed values
+    welcome_secret: String,
+    welcome_key: String,
+    welcome_nonce: String,
+}
+
+pub fn generate_test_vector(ciphersuite: &'static Ciphersuite) -> WelcomeTestVector {
+    let crypto = OpenMlsRustCrypto::default();
+    let joiner_secret = JoinerSecret::random(ciphersuite, &crypto, ProtocolVersion::default());
+    let joiner_secret_bytes = joiner_secret.as_slice().to_vec();
+
+    // Build the PSK secret.
+    let mut psk_ids = Vec::new();
+    let mut psks = Vec::new();
+    let mut psk_values = Vec::new();
+    for _ in 0..(OsRng.next_u32() % 0x10) {
+        let psk_id = PreSharedKeyId::new(
+            Branch,
+            Psk::Branch(BranchPsk {
+                psk_group_id: GroupId::random(&crypto),
+                psk_epoch: GroupEpoch(OsRng.next_u64()),
+            }),
+            crypto.rand().random_vec(13).unwrap(),
+        );
+        let psk = PskSecret::random(ciphersuite, &crypto);
+        psk_values.push(PskValue {
+            psk_id: bytes_to_hex(&psk_id.tls_serialize_detached().unwrap()),
+            psk: bytes_to_hex(psk.secret().as_slice()),
+        });
+        psk_ids.push(psk_id);
+        psks.push(psk.secret().clone());
+    }
+    let psk_secret = PskSecret::new(ciphersuite, &crypto, &psk_ids, &psks).unwrap();
+
+    let key_schedule = KeySchedule::init(ciphersuite, &crypto, joiner_secret, Some(psk_secret));
+    let welcome_secret = key_schedule.welcome(&crypto).unwrap();
+    let (welcome_key, welcome_nonce) = welcome_secret.key_and_nonce(&crypto);
+
+    WelcomeTestVector {
+        cipher_suite: ciphersuite.name() as u16,
+        joiner_secret: bytes_to_hex(&joiner_secret_bytes),
+        psks: psk_values,
+        welcome_secret: bytes_to_hex(welcome_secret.as_slice()),
+        welcome_key: bytes_to_hex(&welcome_key),
+        welcome_nonce: bytes_to_hex(&welcome_nonce),
+    }
+}
+
+#[test]
+fn write_test_vectors() {
+    const NUM_TESTS: usize = 20;
+    let mut tests = Vec::new();
+    for ciphersuite in Config::supported_ciphersuites() {
+        for _ in 0..NUM_TESTS {
+            tests.push(generate_test_vector(ciphersuite));
+        }
+    }
+    write("test_vectors/kat_welcome-new.json", &tests);
+}
+
+pub fn run_test_vector(test_vector: WelcomeTestVector) -> Result<(), WelcomeTestVectorError> {
+    let ciphersuite =
+        CiphersuiteName::try_from(test_vector.cipher_suite).expect("Invalid ciphersuite");
+    let ciphersuite = match Config::ciphersuite(ciphersuite) {
+        Ok(cs) => cs,
+        Err(_) => {
+            log::info!(
+                "Unsupported ciphersuite {} in test vector. Skipping ...",
+                ciphersuite
+            );
+            return Ok(());
+        }
+    };
+    let crypto = OpenMlsRustCrypto::default();
+    log::debug!("Testing test vector for ciphersuite {:?}", ciphersuite);
+    log::trace!("  {:?}", test_vector);
+
+    // Read input values.
+    let joiner_secret = JoinerSecret::from(Secret::from_slice(
+        &hex_to_bytes(&test_vector.joiner_secret),
+        ProtocolVersion::default(),
+        ciphersuite,
+    ));
+    let mut psk_ids = Vec::new();
+    let mut psks = Vec::new();
+    for psk_value in test_vector.psks.iter() {
+        psk_ids.push(
+            PreSharedKeyId::tls_deserialize(&mut hex_to_bytes(&psk_value.psk_id).as_slice())
+                .unwrap(),
+        );
+        psks.push(Secret::from_slice(
+            &hex_to_bytes(&psk_value.psk),
+            ProtocolVersion::default(),
+            ciphersuite,
+        ));
+    }
+    let psk_secret = PskSecret::new(ciphersuite, &crypto, &psk_ids, &psks).unwrap();
+
+    // Compute the welcome secret, key and nonce.
+    let key_schedule = KeySchedule::init(ciphersuite, &crypto, joiner_secret, Some(psk_secret));
+    let welcome_secret = key_schedule.welcome(&crypto).unwrap();
+    if hex_to_bytes(&test_vector.welcome_secret) != welcome_secret.as_slice() {
+        if cfg!(test) {
+            panic!("Welcome secret mismatch");
+        }
+        return Err(WelcomeTestVectorError::WelcomeSecretMismatch);
+    }
+
+    let (welcome_key, welcome_nonce) = welcome_secret.key_and_nonce(&crypto);
+    if hex_to_bytes(&test_vector.welcome_key) != welcome_key {
+        log::error!("  Welcome key mismatch");
+        log::debug!("    Computed: {:x?}", welcome_key);
+        log::debug!(
+            "    Expected: {:x?}",
+            hex_to_bytes(&test_vector.welcome_key)
+        );
+        if cfg!(test) {
+            panic!("Welcome key mismatch");
+        }
+        return Err(WelcomeTestVectorError::WelcomeKeyMismatch);
+    }
+    if hex_to_bytes(&test_vector.welcome_nonce) != welcome_nonce {
+        log::error!("  Welcome nonce mismatch");
+        log::debug!("    Computed: {:x?}", welcome_nonce);
+        log::debug!(
+            "    Expected: {:x?}",
+            hex_to_bytes(&test_vector.welcome_nonce)
+        );
+        if cfg!(test) {
+            panic!("Welcome nonce mismatch");
+        }
+        return Err(WelcomeTestVectorError::WelcomeNonceMismatch);
+    }
+    log::debug!("  Finished welcome test vector verification");
+    Ok(())
+}
+
+#[test]
+fn read_test_vectors() {
+    let tests: Vec<WelcomeTestVector> = read("test_vectors/kat_welcome.json");
+    for test_vector in tests {
+        match run_test_vector(test_vector) {
+            Ok(_) => {}
+            Err(e) => panic!("Error while checking welcome test vector.\n{:?}", e),
+        }
+    }
+}
@@ -140,6 +140,8 @@ mod unit_tests;
 
 #[cfg(any(feature = "test-utils", test))]
 pub mod kat_key_schedule;
+#[cfg(any(feature = "test-utils", test))]
+pub mod kat_welcome;
 
 pub use errors::{ErrorState, KeyScheduleError, PskSecretError};
 pub use psk::{PreSharedKeyId, PreSharedKeys, PskSecret};
@@ -172,7 +174,7 @@ impl CommitSecret {
     ) -> Self {
         // FIXME: remove unwrap
         let secret = path_secret
-            .kdf_expand_label(backend, "path", &[], ciphersuite.hash_length())
+            .expand_with_label(backend, "path", &[], ciphersuite.hash_length())
             .unwrap();
 
         Self { secret }
@@ -253,6 +255,13 @@ pub(crate) struct JoinerSecret {
     secret: Secret,
 }
 
+#[cfg(any(feature = "test-utils", test))]
+impl From<Secret> for JoinerSecret {
+    fn from(secret: Secret) -> Self {
+        Self { secret }
+    }
+}
+
 impl JoinerSecret {
     /// Derive a `JoinerSecret` from an optional `CommitSecret` and an
     /// `EpochSecrets` object, which contains the necessary `InitSecret`. The
@@ -291,7 +300,6 @@ impl JoinerSecret {
         }
     }
 
-    #[cfg(any(feature = "test-utils", test))]
     pub(crate) fn as_slice(&self) -> &[u8] {
         self.secret.as_slice()
     }
@@ -499,6 +507,16 @@ impl WelcomeSecret {
     pub(crate) fn as_slice(&self) -> &[u8] {
         self.secret.as_slice()
     }
+
+    /// Derive the AEAD key and nonce that protect the `GroupInfo` in a
+    /// `Welcome` message as raw bytes, without consuming the secret.
+    #[cfg(any(feature = "test-utils", test))]
+    pub(crate) fn key_and_nonce(&self, backend: &impl OpenMlsCryptoProvider) -> (Vec<u8>, Vec<u8>) {
+        (
+            self.derive_aead_key(backend).as_slice().to_vec(),
+            self.derive_aead_nonce(backend).as_slice().to_vec(),
+        )
+    }
 }
 
 /// An intermediate secret in the key schedule, the `EpochSecret` is used to
@@ -519,7 +537,7 @@ impl EpochSecret {
         // FIXME: remove unwraps
         let secret = intermediate_secret
             .secret
-            .kdf_expand_label(
+            .expand_with_label(
                 backend,
                 "epoch",
                 &group_context.tls_serialize_detached().unwrap(),
@@ -630,7 +648,7 @@ impl ExporterSecret {
         self.secret
             .derive_secret(backend, label)
             .unwrap()
-            .kdf_expand_label(backend, label, context_hash, key_length)
+            .expand_with_label(backend, label, context_hash, key_length)
             .unwrap()
             .as_slice()
             .to_vec()
@@ -743,6 +761,8 @@ impl ConfirmationKey {
         ConfirmationTag(Mac::new(backend, &self.secret, confirmed_transcript_hash))
     }
 
+    /// Create a `ConfirmationKey` from a known secret. This is useful to
+    /// compute confirmation tags under a controlled key in tests.
     #[cfg(any(feature = "test-utils", test))]
     pub(crate) fn from_secret(secret: Secret) -> Self {
         Self { secret }
@@ -869,7 +889,7 @@ impl SenderDataSecret {
         // FIXME: remove unwrap
         let secret = self
             .secret
-            .kdf_expand_label(
+            .expand_with_label(
                 backend,
                 "key",
                 ciphertext_sample,
@@ -894,7 +914,7 @@ impl SenderDataSecret {
         // FIXME: remove unwrap
         let nonce_secret = self
             .secret
-            .kdf_expand_label(
+            .expand_with_label(
                 backend,
                 "nonce",
                 ciphertext_sample,
@@ -304,7 +304,7 @@ impl PskSecret {
                 .tls_serialize_detached()
                 .map_err(|_| PskSecretError::EncodingError)?;
 
-            let psk_input = psk_extracted.kdf_expand_label(
+            let psk_input = psk_extracted.expand_with_label(
                 backend,
                 "derived psk",
                 &psk_label,
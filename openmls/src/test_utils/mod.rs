@@ -1,12 +1,15 @@
 //! Test utilities
 #![allow(dead_code)]
 
+use openmls_traits::OpenMlsCryptoProvider;
 use serde::{self, de::DeserializeOwned, Serialize};
 use std::{
     fs::File,
     io::{BufReader, Write},
 };
 
+use crate::{group::create_commit_params::CreateCommitParams, prelude::*};
+
 pub mod test_framework;
 
 pub(crate) fn write(file_name: &str, obj: impl Serialize) {
@@ -62,6 +65,97 @@ pub(crate) fn hex_to_bytes_option(hex: Option<String>) -> Vec<u8> {
     }
 }
 
+/// Create a group with the first of the `member_names` as creator and add all
+/// other members in a single commit. Returns the creator's group, as well as
+/// the credential bundle and the group of every added member, in the order of
+/// `member_names`. All groups are in the same epoch.
+pub fn setup_group_with_members(
+    ciphersuite: &'static Ciphersuite,
+    member_names: &[&str],
+    backend: &impl OpenMlsCryptoProvider,
+) -> (MlsGroup, Vec<(CredentialBundle, MlsGroup)>) {
+    let framing_parameters = FramingParameters::new(&[], WireFormat::MlsPlaintext);
+    let mut bundles: Vec<(CredentialBundle, KeyPackageBundle)> = member_names
+        .iter()
+        .map(|name| {
+            let credential_bundle = CredentialBundle::new(
+                name.as_bytes().to_vec(),
+                CredentialType::Basic,
+                ciphersuite.signature_scheme(),
+                backend,
+            )
+            .expect("Could not create credential bundle.");
+            let key_package_bundle = KeyPackageBundle::new(
+                &[ciphersuite.name()],
+                &credential_bundle,
+                backend,
+                Vec::new(),
+            )
+            .expect("Could not create key package bundle.");
+            (credential_bundle, key_package_bundle)
+        })
+        .collect();
+    assert!(!bundles.is_empty(), "A group needs at least one member.");
+    let (creator_credential_bundle, creator_key_package_bundle) = bundles.remove(0);
+
+    let mut creator_group = MlsGroup::builder(GroupId::random(backend), creator_key_package_bundle)
+        .build(backend)
+        .expect("Error creating group.");
+    if bundles.is_empty() {
+        return (creator_group, Vec::new());
+    }
+
+    // Add all other members in a single commit.
+    let mut proposal_store = ProposalStore::new();
+    for (_credential_bundle, key_package_bundle) in bundles.iter() {
+        let add_proposal = creator_group
+            .create_add_proposal(
+                framing_parameters,
+                &creator_credential_bundle,
+                key_package_bundle.key_package().clone(),
+                backend,
+            )
+            .expect("Could not create proposal.");
+        proposal_store.add(
+            StagedProposal::from_mls_plaintext(ciphersuite, backend, add_proposal)
+                .expect("Could not create StagedProposal."),
+        );
+    }
+    let params = CreateCommitParams::builder()
+        .framing_parameters(framing_parameters)
+        .credential_bundle(&creator_credential_bundle)
+        .proposal_store(&proposal_store)
+        .force_self_update(false)
+        .build();
+    let (commit, welcome_option, _kpb_option) = creator_group
+        .create_commit(params, backend)
+        .expect("Error creating commit.");
+    let staged_commit = creator_group
+        .stage_commit(&commit, &proposal_store, &[], None, backend)
+        .expect("Error staging commit.");
+    creator_group.merge_commit(staged_commit);
+
+    // Every new member joins from the same Welcome.
+    let welcome = welcome_option.expect("Expected a Welcome message.");
+    let ratchet_tree = creator_group.tree().public_key_tree_copy();
+    let members = bundles
+        .into_iter()
+        .map(|(credential_bundle, key_package_bundle)| {
+            let group = MlsGroup::new_from_welcome(
+                welcome.clone(),
+                Some(ratchet_tree.clone()),
+                key_package_bundle,
+                None,
+                backend,
+            )
+            .expect("Error joining group.");
+            (credential_bundle, group)
+        })
+        .collect();
+
+    (creator_group, members)
+}
+
 #[allow(unused_macros)]
 macro_rules! ctest_ciphersuites {
     ($name:ident, test($param_name:ident: $t:ty) $body:block) => {
@@ -1,4 +1,5 @@
 use crate::{ciphersuite::CryptoError, credentials::CredentialError};
+use tls_codec::Error as TlsCodecError;
 
 implement_error! {
     pub enum TreeError {
@@ -7,12 +8,15 @@ implement_error! {
             InvalidUpdatePath = "The computed update path is invalid.",
             InvalidTree = "The tree is not valid.",
             NotAParentNode = "The node is not a parent node.",
+            TreeTooDeep = "The tree is deeper than the maximum tree depth.",
         }
         Complex {
             PathSecretDecryptionError(CryptoError) =
                 "Error while decrypting `PathSecret`.",
                 CredentialError(CredentialError) =
                     "See [`CredentialError`](`crate::credentials::CredentialError`) for details",
+                CodecError(TlsCodecError) =
+                    "TLS (de)serialization error occurred.",
         }
     }
 }
@@ -24,5 +28,7 @@ implement_error! {
         InputNotParentNode = "The input node is not a parent node.",
         NotAParentNode = "The node is not a parent node.",
         EmptyParentNode = "The parent node was blank.",
+        MissingLeafParentHash = "The leaf's key package has no parent hash extension.",
+        LeafParentHashMismatch = "The parent hash of the leaf doesn't match its direct path.",
     }
 }
@@ -280,8 +280,51 @@ impl RatchetTree {
         Err(ParentHashError::AllChecksFailed)
     }
 
+    /// Verify the parent hash in the `ParentHashExtension` of the key package
+    /// at leaf `index`. It has to match the parent hash of the first non-blank
+    /// node in the leaf's direct path, i.e. the value computed by
+    /// `set_parent_hashes()`. This only holds for the leaf of the member that
+    /// most recently sent an update path through those nodes.
+    pub fn verify_leaf_parent_hash(
+        &self,
+        backend: &impl OpenMlsCryptoProvider,
+        index: LeafIndex,
+    ) -> Result<(), ParentHashError> {
+        let leaf_parent_hash = self.nodes[index]
+            .parent_hash()
+            .ok_or(ParentHashError::MissingLeafParentHash)?;
+
+        // Find the first non-blank node in the direct path and compute its
+        // parent hash with the sibling of the path as co-path child.
+        let tree_size = self.leaf_count();
+        let root = treemath::root(tree_size);
+        let mut child = NodeIndex::from(index);
+        let mut expected_parent_hash = vec![];
+        while child != root {
+            // It is ok to use `unwrap()` here, since we never reach the root
+            let parent = treemath::parent(child, tree_size).unwrap();
+            let parent_node = &self.nodes[parent];
+            if let Some(parent_hash_field) = parent_node.parent_hash() {
+                let sibling = treemath::sibling(child, tree_size).unwrap();
+                expected_parent_hash =
+                    ParentHashInput::new(self, parent, sibling, parent_hash_field)?
+                        .hash(self.ciphersuite, backend);
+                break;
+            }
+            child = parent;
+        }
+
+        if leaf_parent_hash != expected_parent_hash {
+            return Err(ParentHashError::LeafParentHashMismatch);
+        }
+        Ok(())
+    }
+
     /// Verify the parent hashes of the tree nodes. Returns `true` if all parent
     /// hashes have successfully been verified and `false` otherwise.
+    ///
+    /// Leaves are covered through the parent hash extension of their key
+    /// package whenever a parent node is verified through a leaf child.
     pub fn verify_parent_hashes(
         &self,
         backend: &impl OpenMlsCryptoProvider,
@@ -298,39 +341,131 @@ impl RatchetTree {
 
     /// Computes and returns the tree hash
     pub(crate) fn tree_hash(&self, backend: &impl OpenMlsCryptoProvider) -> Vec<u8> {
-        // Recursive helper function to the tree hashes for a node
-        fn node_hash(
-            backend: &impl OpenMlsCryptoProvider,
-            tree: &RatchetTree,
-            index: NodeIndex,
-        ) -> Vec<u8> {
-            let node = &tree.nodes[index];
-            // Depending on the node type, we calculate the hash differently
-            match node.node_type {
-                // For leaf nodes we just need the index and the KeyPackage
-                NodeType::Leaf => {
-                    let leaf_node_hash = LeafNodeHashInput::new(&index, &node.key_package);
-                    leaf_node_hash.hash(tree.ciphersuite, backend)
-                }
-                // For parent nodes we need the hash of the two children as well
-                NodeType::Parent => {
-                    // Unwrapping here is safe, because parent nodes always have children
-                    let left = treemath::left(index).unwrap();
-                    let left_hash = node_hash(backend, tree, left);
-                    let right = treemath::right(index, tree.leaf_count()).unwrap();
-                    let right_hash = node_hash(backend, tree, right);
-                    let parent_node_hash = ParentNodeTreeHashInput::new(
-                        index.as_u32(),
-                        &node.node,
-                        TlsSliceU8(&left_hash),
-                        TlsSliceU8(&right_hash),
-                    );
-                    parent_node_hash.hash(tree.ciphersuite, backend)
-                }
-            }
-        }
         // We start with the root and traverse the tree downwards
         let root = treemath::root(self.leaf_count());
-        node_hash(backend, self, root)
+        self.node_hash(backend, root)
+    }
+
+    /// Recursively computes the tree hash of the subtree rooted at `index`.
+    fn node_hash(&self, backend: &impl OpenMlsCryptoProvider, index: NodeIndex) -> Vec<u8> {
+        let node = &self.nodes[index];
+        // Depending on the node type, we calculate the hash differently
+        match node.node_type {
+            // For leaf nodes we just need the index and the KeyPackage
+            NodeType::Leaf => {
+                let leaf_node_hash = LeafNodeHashInput::new(&index, &node.key_package);
+                leaf_node_hash.hash(self.ciphersuite, backend)
+            }
+            // For parent nodes we need the hash of the two children as well
+            NodeType::Parent => {
+                // Unwrapping here is safe, because parent nodes always have children
+                let left = treemath::left(index).unwrap();
+                let left_hash = self.node_hash(backend, left);
+                let right = treemath::right(index, self.leaf_count()).unwrap();
+                let right_hash = self.node_hash(backend, right);
+                let parent_node_hash = ParentNodeTreeHashInput::new(
+                    index.as_u32(),
+                    &node.node,
+                    TlsSliceU8(&left_hash),
+                    TlsSliceU8(&right_hash),
+                );
+                parent_node_hash.hash(self.ciphersuite, backend)
+            }
+        }
+    }
+
+    /// Computes a [`TreeHashProof`] for the leaf at `leaf_index`.
+    /// The proof contains the leaf, the nodes on its direct path and the tree
+    /// hashes of the copath nodes, which is enough to recompute the tree hash
+    /// without the rest of the tree.
+    /// Returns `TreeError::InvalidArguments` if the leaf is not in the tree.
+    pub fn tree_hash_proof(
+        &self,
+        backend: &impl OpenMlsCryptoProvider,
+        leaf_index: LeafIndex,
+    ) -> Result<TreeHashProof, TreeError> {
+        let size = self.leaf_count();
+        if leaf_index >= size {
+            return Err(TreeError::InvalidArguments);
+        }
+        let leaf_node_index = NodeIndex::from(leaf_index);
+        let root = treemath::root(size);
+
+        let mut direct_path = Vec::new();
+        let mut index = leaf_node_index;
+        while index != root {
+            // We can unwrap here, because we know the index is in the tree and
+            // not the root.
+            let parent = treemath::parent(index, size).unwrap();
+            let sibling = treemath::sibling(index, size).unwrap();
+            direct_path.push(TreeHashProofStep {
+                index: parent,
+                node: self.nodes[parent].clone(),
+                copath_hash: self.node_hash(backend, sibling),
+            });
+            index = parent;
+        }
+
+        Ok(TreeHashProof {
+            ciphersuite: self.ciphersuite,
+            leaf_index: leaf_node_index,
+            leaf: self.nodes[leaf_node_index].clone(),
+            direct_path,
+        })
+    }
+}
+
+/// A proof that a leaf is part of a tree with a given tree hash.
+///
+/// It consists of the leaf, the nodes on the leaf's direct path and the tree
+/// hashes of the nodes on the leaf's copath.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeHashProof {
+    ciphersuite: &'static Ciphersuite,
+    leaf_index: NodeIndex,
+    leaf: Node,
+    direct_path: Vec<TreeHashProofStep>,
+}
+
+/// A node on the direct path of a [`TreeHashProof`] together with the tree
+/// hash of its child on the copath.
+#[derive(Debug, Clone, PartialEq)]
+struct TreeHashProofStep {
+    index: NodeIndex,
+    node: Node,
+    copath_hash: Vec<u8>,
+}
+
+impl TreeHashProof {
+    /// Recomputes the tree hash from the proof and returns `true` if it
+    /// matches `expected_root_hash` and `false` otherwise.
+    pub fn verify(&self, expected_root_hash: &[u8], backend: &impl OpenMlsCryptoProvider) -> bool {
+        if self.leaf.node_type != NodeType::Leaf {
+            return false;
+        }
+        let mut index = self.leaf_index;
+        let mut hash =
+            LeafNodeHashInput::new(&index, &self.leaf.key_package).hash(self.ciphersuite, backend);
+        for step in self.direct_path.iter() {
+            if step.node.node_type != NodeType::Parent {
+                return false;
+            }
+            // The child with the smaller index is the left child.
+            let (left_hash, right_hash) = if index < step.index {
+                (&hash, &step.copath_hash)
+            } else {
+                (&step.copath_hash, &hash)
+            };
+            let parent_hash = ParentNodeTreeHashInput::new(
+                step.index.as_u32(),
+                &step.node.node,
+                TlsSliceU8(left_hash),
+                TlsSliceU8(right_hash),
+            )
+            .hash(self.ciphersuite, backend);
+            index = step.index;
+            hash = parent_hash;
+        }
+        hash == expected_root_hash
     }
 }
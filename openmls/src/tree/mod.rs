@@ -26,7 +26,12 @@ use openmls_traits::crypto::OpenMlsCrypto;
 use openmls_traits::types::HpkeCiphertext;
 use openmls_traits::OpenMlsCryptoProvider;
 use private_tree::PrivateTree;
-use tls_codec::{TlsDeserialize, TlsSerialize, TlsSize, TlsVecU32};
+pub use secret_tree::SecretTreeSnapshot;
+use tls_codec::{
+    Deserialize as TlsDeserializeTrait, Serialize as TlsSerializeTrait, TlsDeserialize,
+    TlsSerialize, TlsSize, TlsVecU32,
+};
+pub use treemath::TreeMathError;
 
 use crate::schedule::{CommitSecret, PreSharedKeys};
 pub(crate) use serde::{
@@ -40,6 +45,10 @@ use std::{collections::HashSet, convert::TryFrom};
 #[cfg(any(feature = "test-utils", test))]
 pub mod tests_and_kats;
 
+/// The default maximum depth of a tree received from another member, which
+/// allows for up to 2^20 leaves.
+pub(crate) const DEFAULT_MAX_TREE_DEPTH: u32 = 20;
+
 #[derive(Debug)]
 #[cfg_attr(test, derive(PartialEq))]
 /// The ratchet tree.
@@ -97,11 +106,33 @@ impl RatchetTree {
     /// Generate a new `RatchetTree` from `Node`s with the client's key package
     /// bundle `kpb`. The client's node must be in the list of nodes and the list
     /// of nodes must contain all nodes of the tree, including intermediates.
+    /// Trees deeper than [`DEFAULT_MAX_TREE_DEPTH`] are rejected.
     pub(crate) fn new_from_nodes(
         backend: &impl OpenMlsCryptoProvider,
         kpb: KeyPackageBundle,
         node_options: &[Option<Node>],
     ) -> Result<RatchetTree, TreeError> {
+        Self::new_from_nodes_with_max_depth(
+            backend,
+            kpb,
+            node_options,
+            DEFAULT_MAX_TREE_DEPTH as usize,
+        )
+    }
+
+    /// Like [`RatchetTree::new_from_nodes()`], but rejects trees deeper than
+    /// `max_tree_depth` with `TreeError::TreeTooDeep` before any nodes are
+    /// processed.
+    pub(crate) fn new_from_nodes_with_max_depth(
+        backend: &impl OpenMlsCryptoProvider,
+        kpb: KeyPackageBundle,
+        node_options: &[Option<Node>],
+        max_tree_depth: usize,
+    ) -> Result<RatchetTree, TreeError> {
+        if treemath::tree_depth(node_options.len()) > max_tree_depth {
+            return Err(TreeError::TreeTooDeep);
+        }
+
         // Build a full set of nodes for the tree based on the potentially incomplete
         // input nodes.
         let mut nodes: Vec<Node> = Vec::with_capacity(node_options.len());
@@ -168,6 +199,66 @@ impl RatchetTree {
         self.public_key_tree().iter().map(|&n| n.cloned()).collect()
     }
 
+    /// Bring the tree into its canonical form, so that the tree hash and the
+    /// nodes of the ratchet tree extension are byte-identical with those of
+    /// other implementations:
+    ///  - leaf nodes don't carry a parent node and parent nodes don't carry a
+    ///    key package
+    ///  - the unmerged leaves of parent nodes are sorted and free of duplicates
+    pub fn canonicalize(&mut self) {
+        for node in self.nodes.iter_mut() {
+            match node.node_type {
+                NodeType::Leaf => node.node = None,
+                NodeType::Parent => {
+                    node.key_package = None;
+                    if let Some(parent_node) = node.node.as_mut() {
+                        let mut unmerged_leaves = parent_node.unmerged_leaves().to_vec();
+                        unmerged_leaves.sort_unstable();
+                        unmerged_leaves.dedup();
+                        parent_node.unmerged_leaves = unmerged_leaves.as_slice().into();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Serialize the node at `index` as `optional<Node>`, i.e. in the same
+    /// encoding that is used for the nodes of the ratchet tree extension.
+    /// Returns `None` if `index` is outside of the tree.
+    pub fn serialize_node(&self, index: NodeIndex) -> Option<Vec<u8>> {
+        let node = self.nodes.get(index.as_usize())?;
+        let node_option = if node.is_blank() {
+            None
+        } else {
+            Some(node.clone())
+        };
+        node_option.tls_serialize_detached().ok()
+    }
+
+    /// Replace the node at `index` with a node that was serialized with
+    /// [`RatchetTree::serialize_node()`]. The updated node is taken into
+    /// account the next time the tree hash is computed.
+    ///
+    /// Returns `TreeError::InvalidArguments` if `index` is outside of the tree
+    /// or if the type of the node doesn't match its position in the tree.
+    pub fn apply_node_update(&mut self, index: NodeIndex, bytes: &[u8]) -> Result<(), TreeError> {
+        if index >= self.tree_size() {
+            return Err(TreeError::InvalidArguments);
+        }
+        let node = match Option::<Node>::tls_deserialize(&mut &*bytes)? {
+            Some(node) => {
+                if node.node_type.is_parent() != index.is_parent() {
+                    return Err(TreeError::InvalidArguments);
+                }
+                node
+            }
+            None if index.is_parent() => Node::new_blank_parent_node(),
+            None => Node::new_leaf(None),
+        };
+        self.nodes[index] = node;
+        Ok(())
+    }
+
     /// Returns the number of leaves in a tree
     pub fn leaf_count(&self) -> LeafIndex {
         treemath::leaf_count(self.tree_size())
@@ -186,6 +277,33 @@ impl RatchetTree {
         })
     }
 
+    /// Returns the nodes on the direct path of `leaf`, from its parent up to
+    /// the root, together with their public HPKE keys. The key is `None` for
+    /// blank nodes.
+    /// Returns a `TreeMathError` if `leaf` is not in the tree.
+    pub fn direct_path_nodes(
+        &self,
+        leaf: LeafIndex,
+    ) -> Result<Vec<(NodeIndex, Option<HpkePublicKey>)>, TreeMathError> {
+        Ok(treemath::leaf_direct_path(leaf, self.leaf_count())?
+            .into_iter()
+            .map(|index| (index, self.nodes[index].public_hpke_key().cloned()))
+            .collect())
+    }
+
+    /// Returns the size of the resolution of every node in the copath of the
+    /// own leaf, i.e. the number of path secret encryptions an update path
+    /// would contain for each of its nodes.
+    pub(crate) fn copath_resolution_sizes(&self) -> Vec<usize> {
+        // The own leaf is always within the tree, so computing the copath
+        // can't fail.
+        treemath::copath(self.own_node_index(), self.leaf_count())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|copath_node| self.resolve(copath_node, &HashSet::new()).len())
+            .collect()
+    }
+
     /// Compute the resolution for a given node index. Leaves listed in the
     /// `exclusion_list` are subtracted from the final resolution.
     fn resolve(&self, index: NodeIndex, exclusion_list: &HashSet<&LeafIndex>) -> Vec<NodeIndex> {
@@ -816,14 +934,18 @@ impl RatchetTree {
         // Extract KeyPackages from proposals
         let key_packages: Vec<&KeyPackage> =
             add_proposals.iter().map(|a| a.key_package()).collect();
-        // Add new members to tree
+        // Add new members to tree. Since removes were processed first, new
+        // members fill the leftmost blank leaves, including the ones freed by
+        // the removes, before the tree is extended.
         let added_members = self.add_nodes(&key_packages);
 
-        // Prepare invitations
-        let mut invitation_list = Vec::new();
-        for (i, added) in added_members.iter().enumerate() {
-            invitation_list.push((added.0, add_proposals.get(i).unwrap().clone()));
-        }
+        // Prepare invitations. Only the members added by this commit are
+        // invited, so that existing members never get Welcome secrets.
+        let invitation_list: Vec<(LeafIndex, AddProposal)> = added_members
+            .iter()
+            .zip(add_proposals.iter())
+            .map(|((leaf_index, _), add_proposal)| (*leaf_index, add_proposal.clone()))
+            .collect();
 
         // Process PSK proposals
         let psks: Vec<PreSharedKeyId> = proposal_queue
@@ -925,14 +1047,18 @@ impl RatchetTree {
         // Extract KeyPackages from proposals
         let key_packages: Vec<&KeyPackage> =
             add_proposals.iter().map(|a| a.key_package()).collect();
-        // Add new members to tree
+        // Add new members to tree. Since removes were processed first, new
+        // members fill the leftmost blank leaves, including the ones freed by
+        // the removes, before the tree is extended.
         let added_members = self.add_nodes(&key_packages);
 
-        // Prepare invitations
-        let mut invitation_list = Vec::new();
-        for (i, added) in added_members.iter().enumerate() {
-            invitation_list.push((added.0, add_proposals.get(i).unwrap().clone()));
-        }
+        // Prepare invitations. Only the members added by this commit are
+        // invited, so that existing members never get Welcome secrets.
+        let invitation_list: Vec<(LeafIndex, AddProposal)> = added_members
+            .iter()
+            .zip(add_proposals.iter())
+            .map(|((leaf_index, _), add_proposal)| (*leaf_index, add_proposal.clone()))
+            .collect();
 
         // Process PSK proposals
         let psks: Vec<PreSharedKeyId> = proposal_queue
@@ -134,7 +134,7 @@ impl PrivateTree {
             // FIXME: remove unwrap
             vec![PathSecret {
                 path_secret: leaf_secret
-                    .kdf_expand_label(backend, "path", &[], ciphersuite.hash_length())
+                    .expand_with_label(backend, "path", &[], ciphersuite.hash_length())
                     .unwrap(),
             }]
         };
@@ -179,7 +179,7 @@ impl PrivateTree {
             // FIXME: remove unwrap
             let path_secret = path_secrets[i - 1]
                 .path_secret
-                .kdf_expand_label(backend, "path", &[], hash_len)
+                .expand_with_label(backend, "path", &[], hash_len)
                 .unwrap();
             path_secrets.push(PathSecret { path_secret });
         }
@@ -241,7 +241,7 @@ impl PrivateTree {
             // FIXME: remove unwrap
             let node_secret = path_secret
                 .path_secret
-                .kdf_expand_label(backend, "node", &[], hash_len)
+                .expand_with_label(backend, "node", &[], hash_len)
                 .unwrap();
             let keypair = backend
                 .crypto()
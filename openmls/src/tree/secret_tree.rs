@@ -12,6 +12,7 @@ implement_error! {
         TooDistantInThePast = "Generation is too old to be processed.",
         TooDistantInTheFuture = "Generation is too far in the future to be processed.",
         IndexOutOfBounds = "Index out of bounds",
+        SnapshotSizeMismatch = "The snapshot was taken from a secret tree of a different size.",
     }
 }
 
@@ -59,7 +60,7 @@ pub(crate) fn derive_tree_secret(
     // FIXME: remove unwraps
     let serialized_tree_context = tree_context.tls_serialize_detached().unwrap();
     secret
-        .kdf_expand_label(backend, label, &serialized_tree_context, length)
+        .expand_with_label(backend, label, &serialized_tree_context, length)
         .unwrap()
 }
 
@@ -84,6 +85,26 @@ pub struct SecretTree {
     size: LeafIndex,
 }
 
+/// A snapshot of the ratchet state of a [`SecretTree`], i.e. the node secrets
+/// that haven't been consumed yet, as well as the generation and the retained
+/// secrets of every sender ratchet. The snapshot can be persisted on its own,
+/// without serializing the rest of the group state.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct SecretTreeSnapshot {
+    nodes: Vec<Option<SecretTreeNode>>,
+    handshake_sender_ratchets: Vec<Option<SenderRatchet>>,
+    application_sender_ratchets: Vec<Option<SenderRatchet>>,
+    size: LeafIndex,
+}
+
+impl SecretTreeSnapshot {
+    /// Get the number of leaves of the tree the snapshot was taken from.
+    pub(crate) fn size(&self) -> LeafIndex {
+        self.size
+    }
+}
+
 impl SecretTree {
     /// Creates a new SecretTree based on an `encryption_secret` and group size
     /// `size`. The inner nodes of the tree and the SenderRatchets only get
@@ -105,6 +126,32 @@ impl SecretTree {
         }
     }
 
+    /// Take a snapshot of the current ratchet state of the tree.
+    pub(crate) fn snapshot(&self) -> SecretTreeSnapshot {
+        SecretTreeSnapshot {
+            nodes: self.nodes.clone(),
+            handshake_sender_ratchets: self.handshake_sender_ratchets.clone(),
+            application_sender_ratchets: self.application_sender_ratchets.clone(),
+            size: self.size,
+        }
+    }
+
+    /// Restore a tree from a `snapshot`. Note that secrets that were consumed
+    /// after the snapshot was taken become available again.
+    pub(crate) fn restore(snapshot: SecretTreeSnapshot) -> Self {
+        SecretTree {
+            nodes: snapshot.nodes,
+            handshake_sender_ratchets: snapshot.handshake_sender_ratchets,
+            application_sender_ratchets: snapshot.application_sender_ratchets,
+            size: snapshot.size,
+        }
+    }
+
+    /// Get the number of leaves of the tree.
+    pub(crate) fn size(&self) -> LeafIndex {
+        self.size
+    }
+
     /// Get current generation for a specific SenderRatchet
     #[cfg(test)]
     pub(crate) fn generation(&self, index: LeafIndex, secret_type: SecretType) -> u32 {
@@ -228,6 +275,31 @@ impl SecretTree {
         sender_ratchet.secret_for_decryption(ciphersuite, backend, generation)
     }
 
+    /// Derive and retain the application secrets of `sender` for all
+    /// generations up to `up_to`, so that a burst of out-of-order application
+    /// messages from that sender can be decrypted without ratcheting for each
+    /// message. Returns an error if the index is out of bounds or `up_to` is
+    /// too distant in the future.
+    pub(crate) fn prepare_generations(
+        &mut self,
+        ciphersuite: &Ciphersuite,
+        backend: &impl OpenMlsCryptoProvider,
+        sender: LeafIndex,
+        up_to: u32,
+    ) -> Result<(), SecretTreeError> {
+        if sender >= self.size {
+            return Err(SecretTreeError::IndexOutOfBounds);
+        }
+        if self
+            .ratchet_opt(sender, SecretType::ApplicationSecret)?
+            .is_none()
+        {
+            self.initialize_sender_ratchets(ciphersuite, backend, sender)?;
+        }
+        self.ratchet_mut(sender, SecretType::ApplicationSecret)
+            .prepare_generations(ciphersuite, backend, up_to)
+    }
+
     /// Return the next RatchetSecrets that should be used for encryption and
     /// then increments the generation.
     pub(crate) fn secret_for_encryption(
@@ -37,8 +37,10 @@ impl SenderRatchet {
         if generation > (self.generation + MAXIMUM_FORWARD_DISTANCE) {
             return Err(SecretTreeError::TooDistantInTheFuture);
         }
-        // If generation id too distant in the past
-        if generation < self.generation && (self.generation - generation) >= OUT_OF_ORDER_TOLERANCE
+        // If generation id too distant in the past, i.e. outside the window of
+        // retained secrets
+        if generation < self.generation
+            && (self.generation - generation) as usize >= self.past_secrets.len()
         {
             return Err(SecretTreeError::TooDistantInThePast);
         }
@@ -52,7 +54,9 @@ impl SenderRatchet {
         // If generation is in the future
         } else {
             for _ in 0..(generation - self.generation) {
-                if self.past_secrets.len() == OUT_OF_ORDER_TOLERANCE as usize {
+                // The window may be larger than the tolerance after preparing
+                // generations.
+                while self.past_secrets.len() >= OUT_OF_ORDER_TOLERANCE as usize {
                     self.past_secrets.remove(0);
                 }
                 let new_secret =
@@ -65,6 +69,27 @@ impl SenderRatchet {
             Ok(ratchet_secrets)
         }
     }
+    /// Ratchets forward to generation `up_to` and retains the secrets of all
+    /// generations in between, so that they can be decrypted in any order
+    /// without ratcheting again. Returns an error if `up_to` is too distant in
+    /// the future.
+    pub(crate) fn prepare_generations(
+        &mut self,
+        ciphersuite: &Ciphersuite,
+        backend: &impl OpenMlsCryptoProvider,
+        up_to: u32,
+    ) -> Result<(), SecretTreeError> {
+        if up_to > (self.generation + MAXIMUM_FORWARD_DISTANCE) {
+            return Err(SecretTreeError::TooDistantInTheFuture);
+        }
+        while self.generation < up_to {
+            let new_secret =
+                self.ratchet_secret(ciphersuite, backend, self.past_secrets.last().unwrap());
+            self.past_secrets.push(new_secret);
+            self.generation += 1;
+        }
+        Ok(())
+    }
     /// Gets a secret from the SenderRatchet and ratchets forward
     pub fn secret_for_encryption(
         &mut self,
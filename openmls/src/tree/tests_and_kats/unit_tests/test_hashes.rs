@@ -80,3 +80,228 @@ fn test_parent_hash() {
         assert!(leaf_swap_parent_hash != original_parent_hash);
     }
 }
+
+#[test]
+fn test_tree_hash_proof() {
+    let crypto = OpenMlsRustCrypto::default();
+    for ciphersuite in Config::supported_ciphersuites() {
+        // Check a full tree and a truncated tree
+        for &leaves in &[8usize, 5] {
+            let mut nodes = vec![];
+            let mut key_package_bundles = vec![];
+            for i in 0..leaves {
+                let credential_bundle = CredentialBundle::new(
+                    vec![i as u8],
+                    CredentialType::Basic,
+                    ciphersuite.signature_scheme(),
+                    &crypto,
+                )
+                .unwrap();
+                let key_package_bundle = KeyPackageBundle::new(
+                    &[ciphersuite.name()],
+                    &credential_bundle,
+                    &crypto,
+                    vec![],
+                )
+                .unwrap();
+                nodes.push(Some(Node::new_leaf(Some(
+                    key_package_bundle.key_package().clone(),
+                ))));
+                key_package_bundles.push(key_package_bundle);
+                if i != leaves - 1 {
+                    nodes.push(None);
+                }
+            }
+
+            let key_package_bundle = key_package_bundles.remove(0);
+            let mut tree =
+                RatchetTree::new_from_nodes(&crypto, key_package_bundle, &nodes).unwrap();
+            let tree_hash = tree.tree_hash(&crypto);
+
+            for leaf in 0..leaves {
+                let proof = tree
+                    .tree_hash_proof(&crypto, LeafIndex::from(leaf))
+                    .expect("Could not compute tree hash proof.");
+                assert!(proof.verify(&tree_hash, &crypto));
+                assert!(!proof.verify(&[0u8; 32], &crypto));
+            }
+
+            // Leaves outside of the tree don't have a proof.
+            assert_eq!(
+                tree.tree_hash_proof(&crypto, LeafIndex::from(leaves))
+                    .expect_err("Computed a proof for a leaf outside of the tree."),
+                TreeError::InvalidArguments
+            );
+
+            // The proof for leaf 3 doesn't verify once another leaf changed.
+            let proof = tree
+                .tree_hash_proof(&crypto, LeafIndex::from(3usize))
+                .expect("Could not compute tree hash proof.");
+            tree.nodes.swap(0, 2);
+            assert!(!proof.verify(&tree.tree_hash(&crypto), &crypto));
+        }
+    }
+}
+
+#[test]
+fn test_node_updates() {
+    let crypto = OpenMlsRustCrypto::default();
+    for ciphersuite in Config::supported_ciphersuites() {
+        const LEAVES: usize = 4;
+        let mut nodes = vec![];
+        let mut key_package_bundles = vec![];
+        for i in 0..LEAVES {
+            let credential_bundle = CredentialBundle::new(
+                vec![i as u8],
+                CredentialType::Basic,
+                ciphersuite.signature_scheme(),
+                &crypto,
+            )
+            .unwrap();
+            let key_package_bundle =
+                KeyPackageBundle::new(&[ciphersuite.name()], &credential_bundle, &crypto, vec![])
+                    .unwrap();
+            nodes.push(Some(Node::new_leaf(Some(
+                key_package_bundle.key_package().clone(),
+            ))));
+            key_package_bundles.push(key_package_bundle);
+            if i != LEAVES - 1 {
+                nodes.push(None);
+            }
+        }
+
+        let key_package_bundle = key_package_bundles.remove(0);
+        let mut sender_tree =
+            RatchetTree::new_from_nodes(&crypto, key_package_bundle.clone(), &nodes).unwrap();
+        let mut receiver_tree =
+            RatchetTree::new_from_nodes(&crypto, key_package_bundle, &nodes).unwrap();
+        let original_tree_hash = sender_tree.tree_hash(&crypto);
+
+        // Replace the key package of the last leaf and blank the second leaf
+        // in the sender's tree.
+        let updated_leaf = NodeIndex::from(6usize);
+        let blanked_leaf = NodeIndex::from(2usize);
+        let credential_bundle = CredentialBundle::new(
+            vec![42],
+            CredentialType::Basic,
+            ciphersuite.signature_scheme(),
+            &crypto,
+        )
+        .unwrap();
+        let new_key_package_bundle =
+            KeyPackageBundle::new(&[ciphersuite.name()], &credential_bundle, &crypto, vec![])
+                .unwrap();
+        sender_tree.nodes[updated_leaf] =
+            Node::new_leaf(Some(new_key_package_bundle.key_package().clone()));
+        sender_tree.nodes[blanked_leaf].blank();
+
+        // Ship only the changed nodes to the receiver.
+        for &index in &[updated_leaf, blanked_leaf] {
+            let bytes = sender_tree
+                .serialize_node(index)
+                .expect("Could not serialize node.");
+            receiver_tree
+                .apply_node_update(index, &bytes)
+                .expect("Could not apply node update.");
+        }
+
+        let updated_tree_hash = receiver_tree.tree_hash(&crypto);
+        assert_ne!(original_tree_hash, updated_tree_hash);
+        assert_eq!(sender_tree.tree_hash(&crypto), updated_tree_hash);
+
+        // The result is the same as re-building the tree from a full copy of
+        // the sender's tree, here from the perspective of the third leaf.
+        let full_tree = RatchetTree::new_from_nodes(
+            &crypto,
+            key_package_bundles.remove(1),
+            &sender_tree.public_key_tree_copy(),
+        )
+        .unwrap();
+        assert_eq!(full_tree.tree_hash(&crypto), updated_tree_hash);
+
+        // Nodes outside of the tree can't be serialized or updated.
+        let outside = NodeIndex::from(7usize);
+        assert!(sender_tree.serialize_node(outside).is_none());
+        let bytes = sender_tree.serialize_node(updated_leaf).unwrap();
+        assert_eq!(
+            receiver_tree.apply_node_update(outside, &bytes),
+            Err(TreeError::InvalidArguments)
+        );
+        // A leaf can't be put at the position of a parent node.
+        assert_eq!(
+            receiver_tree.apply_node_update(NodeIndex::from(1usize), &bytes),
+            Err(TreeError::InvalidArguments)
+        );
+    }
+}
+
+#[test]
+fn test_canonicalize() {
+    let crypto = OpenMlsRustCrypto::default();
+    for ciphersuite in Config::supported_ciphersuites() {
+        const LEAVES: usize = 4;
+        let mut nodes = vec![];
+        let mut key_package_bundles = vec![];
+        for i in 0..LEAVES {
+            let credential_bundle = CredentialBundle::new(
+                vec![i as u8],
+                CredentialType::Basic,
+                ciphersuite.signature_scheme(),
+                &crypto,
+            )
+            .unwrap();
+            let key_package_bundle =
+                KeyPackageBundle::new(&[ciphersuite.name()], &credential_bundle, &crypto, vec![])
+                    .unwrap();
+            nodes.push(Some(Node::new_leaf(Some(
+                key_package_bundle.key_package().clone(),
+            ))));
+            key_package_bundles.push(key_package_bundle);
+            if i != LEAVES - 1 {
+                nodes.push(None);
+            }
+        }
+
+        let mut tree =
+            RatchetTree::new_from_nodes(&crypto, key_package_bundles.remove(0), &nodes).unwrap();
+
+        // Put a parent node with unmerged leaves at the root.
+        let root = NodeIndex::from(3usize);
+        let public_key = crypto
+            .crypto()
+            .derive_hpke_keypair(
+                ciphersuite.hpke_config(),
+                Secret::random(ciphersuite, &crypto, None).as_slice(),
+            )
+            .public
+            .into();
+        let canonical_leaves = [LeafIndex::from(1u32), LeafIndex::from(3u32)];
+        tree.nodes[root] = Node {
+            node_type: NodeType::Parent,
+            key_package: None,
+            node: Some(ParentNode::new(public_key, &canonical_leaves, &[])),
+        };
+        let canonical_tree_hash = tree.tree_hash(&crypto);
+
+        // Canonicalizing a canonical tree doesn't change anything.
+        tree.canonicalize();
+        assert_eq!(tree.tree_hash(&crypto), canonical_tree_hash);
+
+        // Unsorted and duplicate unmerged leaves change the hash until the
+        // tree is canonicalized again.
+        let unsorted_leaves = [
+            LeafIndex::from(3u32),
+            LeafIndex::from(1u32),
+            LeafIndex::from(3u32),
+        ];
+        tree.nodes[root].node.as_mut().unwrap().unmerged_leaves = unsorted_leaves[..].into();
+        assert_ne!(tree.tree_hash(&crypto), canonical_tree_hash);
+
+        tree.canonicalize();
+        assert_eq!(
+            tree.nodes[root].node.as_ref().unwrap().unmerged_leaves(),
+            &canonical_leaves
+        );
+        assert_eq!(tree.tree_hash(&crypto), canonical_tree_hash);
+    }
+}
@@ -99,3 +99,51 @@ fn create_private_tree_from_secret() {
         );
     }
 }
+
+#[test]
+fn continue_path_secrets_is_deterministic() {
+    use crate::{config::*, messages::PathSecret};
+    const PATH_LENGTH: usize = 33;
+    let crypto = OpenMlsRustCrypto::default();
+    for ciphersuite in Config::supported_ciphersuites() {
+        let (_key_package_bundle, own_index, direct_path) = setup(ciphersuite, PATH_LENGTH);
+        let start_secret = PathSecret::from(Secret::random(ciphersuite, &crypto, None));
+
+        // Derive the chain twice from the same path secret, e.g. when
+        // recovering from a commit that wasn't applied correctly.
+        let mut private_tree = PrivateTree::new(own_index);
+        let public_keys = private_tree.continue_path_secrets(
+            ciphersuite,
+            &crypto,
+            start_secret.clone(),
+            &direct_path,
+        );
+        let mut rederived_private_tree = PrivateTree::new(own_index);
+        let rederived_public_keys = rederived_private_tree.continue_path_secrets(
+            ciphersuite,
+            &crypto,
+            start_secret,
+            &direct_path,
+        );
+
+        assert_eq!(public_keys.len(), direct_path.len());
+        assert_eq!(public_keys, rederived_public_keys);
+        assert_eq!(
+            private_tree.path_secrets(),
+            rederived_private_tree.path_secrets()
+        );
+        assert_eq!(
+            private_tree.commit_secret(),
+            rederived_private_tree.commit_secret()
+        );
+        assert_eq!(private_tree, rederived_private_tree);
+
+        test_private_tree(
+            &rederived_private_tree,
+            &direct_path,
+            &rederived_public_keys,
+            ciphersuite,
+            &crypto,
+        );
+    }
+}
@@ -202,3 +202,129 @@ fn secret_tree() {
     );
     println!("Secret tree: {:?}", secret_tree);
 }
+
+// This tests that prepared generations can be decrypted in any order, even
+// beyond the out-of-order tolerance.
+#[test]
+fn prepare_generations() {
+    let crypto = OpenMlsRustCrypto::default();
+    for ciphersuite in Config::supported_ciphersuites() {
+        let secret_bytes = crypto.rand().random_vec(ciphersuite.hash_length()).unwrap();
+        let encryption_secret =
+            || EncryptionSecret::from_slice(&secret_bytes, ProtocolVersion::default(), ciphersuite);
+        let mut sender_tree = SecretTree::new(encryption_secret(), LeafIndex::from(2u32));
+        let mut receiver_tree = SecretTree::new(encryption_secret(), LeafIndex::from(2u32));
+        let sender = LeafIndex::from(0u32);
+
+        let sender_secrets: Vec<_> = (0..10)
+            .map(|_| {
+                sender_tree
+                    .secret_for_encryption(
+                        ciphersuite,
+                        &crypto,
+                        sender,
+                        SecretType::ApplicationSecret,
+                    )
+                    .expect("Index out of bounds.")
+                    .1
+            })
+            .collect();
+
+        receiver_tree
+            .prepare_generations(ciphersuite, &crypto, sender, 9)
+            .expect("Error preparing generations.");
+        assert_eq!(
+            receiver_tree.generation(sender, SecretType::ApplicationSecret),
+            9
+        );
+
+        for generation in [7, 0, 9, 3] {
+            assert_eq!(
+                receiver_tree
+                    .secret_for_decryption(
+                        ciphersuite,
+                        &crypto,
+                        sender,
+                        SecretType::ApplicationSecret,
+                        generation,
+                    )
+                    .expect("Error getting decryption secret."),
+                sender_secrets[generation as usize]
+            );
+        }
+
+        // Preparing generations is bounded by the maximum forward distance.
+        assert_eq!(
+            receiver_tree.prepare_generations(ciphersuite, &crypto, sender, 10_000),
+            Err(SecretTreeError::TooDistantInTheFuture)
+        );
+        assert_eq!(
+            receiver_tree.prepare_generations(ciphersuite, &crypto, LeafIndex::from(2u32), 1),
+            Err(SecretTreeError::IndexOutOfBounds)
+        );
+    }
+}
+
+// This tests that a restored snapshot continues decrypting where the
+// snapshotted tree left off.
+#[test]
+fn snapshot_and_restore() {
+    let crypto = OpenMlsRustCrypto::default();
+    for ciphersuite in Config::supported_ciphersuites() {
+        let secret_bytes = crypto.rand().random_vec(ciphersuite.hash_length()).unwrap();
+        let encryption_secret =
+            || EncryptionSecret::from_slice(&secret_bytes, ProtocolVersion::default(), ciphersuite);
+        let mut sender_tree = SecretTree::new(encryption_secret(), LeafIndex::from(2u32));
+        let mut receiver_tree = SecretTree::new(encryption_secret(), LeafIndex::from(2u32));
+        let sender = LeafIndex::from(1u32);
+
+        let sender_secrets: Vec<_> = (0..6)
+            .map(|_| {
+                sender_tree
+                    .secret_for_encryption(
+                        ciphersuite,
+                        &crypto,
+                        sender,
+                        SecretType::ApplicationSecret,
+                    )
+                    .expect("Index out of bounds.")
+                    .1
+            })
+            .collect();
+        let decrypt = |tree: &mut SecretTree, generation: u32| {
+            tree.secret_for_decryption(
+                ciphersuite,
+                &crypto,
+                sender,
+                SecretType::ApplicationSecret,
+                generation,
+            )
+            .expect("Error getting decryption secret.")
+        };
+
+        // The receiver processes the first messages and takes a snapshot,
+        // which is persisted on its own.
+        for generation in 0..3 {
+            assert_eq!(
+                decrypt(&mut receiver_tree, generation),
+                sender_secrets[generation as usize]
+            );
+        }
+        let snapshot = receiver_tree.snapshot();
+        let serialized_snapshot =
+            serde_json::to_vec(&snapshot).expect("Error serializing snapshot.");
+
+        // The restored tree has the same ratchet state and can decrypt the
+        // subsequent messages.
+        let snapshot: SecretTreeSnapshot =
+            serde_json::from_slice(&serialized_snapshot).expect("Error deserializing snapshot.");
+        let mut restored_tree = SecretTree::restore(snapshot);
+        assert_eq!(restored_tree, receiver_tree);
+        for generation in 3..6 {
+            assert_eq!(
+                decrypt(&mut restored_tree, generation),
+                sender_secrets[generation as usize]
+            );
+        }
+    }
+}
@@ -107,3 +107,63 @@ fn invalid_inputs() {
         treemath::parent(1000u32.into(), 100u32.into())
     );
 }
+
+#[test]
+fn test_direct_path_nodes() {
+    let crypto = OpenMlsRustCrypto::default();
+    for ciphersuite in Config::supported_ciphersuites() {
+        let credential_bundle = CredentialBundle::new(
+            b"Tree creator".to_vec(),
+            CredentialType::Basic,
+            ciphersuite.signature_scheme(),
+            &crypto,
+        )
+        .unwrap();
+        let mut key_package_bundles = (0..8)
+            .map(|_| {
+                KeyPackageBundle::new(&[ciphersuite.name()], &credential_bundle, &crypto, vec![])
+                    .unwrap()
+            })
+            .collect::<Vec<KeyPackageBundle>>();
+
+        // Build a tree with 8 leaves. All parent nodes are blank.
+        let mut tree = RatchetTree::new(&crypto, key_package_bundles.remove(0));
+        let key_packages: Vec<&KeyPackage> = key_package_bundles
+            .iter()
+            .map(|kpb| kpb.key_package())
+            .collect();
+        let _ = tree.add_nodes(&key_packages);
+        assert_eq!(tree.leaf_count(), LeafIndex::from(8u32));
+
+        // Populate the parent of leaf 0 and the root, but leave the node in
+        // between blank.
+        let public_key = key_packages[0].hpke_init_key().clone();
+        for index in [1u32, 7] {
+            tree.nodes[NodeIndex::from(index)] = Node {
+                node_type: NodeType::Parent,
+                node: Some(ParentNode {
+                    parent_hash: vec![].into(),
+                    public_key: public_key.clone(),
+                    unmerged_leaves: vec![].into(),
+                }),
+                key_package: None,
+            };
+        }
+
+        let direct_path = tree.direct_path_nodes(LeafIndex::from(0u32)).unwrap();
+        assert_eq!(
+            direct_path,
+            vec![
+                (NodeIndex::from(1u32), Some(public_key.clone())),
+                (NodeIndex::from(3u32), None),
+                (NodeIndex::from(7u32), Some(public_key)),
+            ]
+        );
+
+        // Leaves outside of the tree are rejected.
+        assert_eq!(
+            tree.direct_path_nodes(LeafIndex::from(8u32)),
+            Err(TreeMathError::LeafNotInTree)
+        );
+    }
+}
@@ -43,6 +43,11 @@ pub(crate) fn node_width(n: usize) -> usize {
     }
 }
 
+/// Returns the depth of a tree with `width` nodes, i.e. the level of its root.
+pub(crate) fn tree_depth(width: usize) -> usize {
+    log2(width)
+}
+
 pub(crate) fn root(size: LeafIndex) -> NodeIndex {
     let n = size.as_usize();
     let w = node_width(n);
@@ -34,6 +34,7 @@ fn create_encoding_test_setup() -> TestSetup {
                 add_ratchet_tree_extension: true,
                 padding_block_size: 10,
                 additional_as_epochs: 0,
+                ..MlsGroupConfig::default()
             },
             members: vec![alice_config.clone(), bob_config.clone()],
         };
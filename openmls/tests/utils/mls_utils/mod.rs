@@ -157,7 +157,7 @@ pub(crate) fn setup(config: TestSetupConfig) -> TestSetup {
             GroupId::from_slice(&group_id.to_be_bytes()),
             initial_key_package_bundle,
         )
-        .with_config(group_config.config)
+        .with_config(group_config.config.clone())
         .build(&crypto)
         .expect("Error creating new MlsGroup");
         let mut proposal_list = Vec::new();